@@ -1,24 +1,400 @@
 use hyper_util::rt::tokio::TokioIo;
-use tonic::transport::{Endpoint, Uri};
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint, Identity, Uri};
 use tower::service_fn;
-use std::path::PathBuf;
-use std::process::{Command, Child};
+use std::path::{Path, PathBuf};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::process::{Command, Child, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::Mutex;
-use once_cell::sync::Lazy;
+use std::time::Duration;
+use once_cell::sync::{Lazy, OnceCell};
+use tauri::{Emitter, Manager};
+use tokio_util::sync::CancellationToken;
 
 // Global state to track the ConnectToolCore process
 static CORE_PROCESS: Lazy<Mutex<Option<Child>>> = Lazy::new(|| Mutex::new(None));
 
+// PID of the child in CORE_PROCESS, 0 when there is none. Lets status checks answer
+// while a start or stop holds the lock.
+static MANAGED_CORE_PID: AtomicU32 = AtomicU32::new(0);
+
+/// When the managed core was spawned
+#[derive(Clone, Copy)]
+struct CoreStartTime {
+    instant: std::time::Instant,
+    time: chrono::DateTime<chrono::Utc>,
+}
+
+// Start time of the managed core, kept in step with CORE_PROCESS
+static CORE_STARTED_AT: Lazy<Mutex<Option<CoreStartTime>>> = Lazy::new(|| Mutex::new(None));
+
+// Whether the core is expected to be running, i.e. started by us and not stopped since
+static CORE_SHOULD_RUN: AtomicBool = AtomicBool::new(false);
+
+// Whether the supervisor should restart the core after an unexpected exit
+static CORE_AUTORESTART: AtomicBool = AtomicBool::new(false);
+static CORE_SUPERVISOR_STARTED: AtomicBool = AtomicBool::new(false);
+
+// Set while a start, stop or restart command is running
+static CORE_TRANSITION: AtomicBool = AtomicBool::new(false);
+
+// Handle to the running app, used to emit events from background threads
+static APP_HANDLE: OnceCell<tauri::AppHandle> = OnceCell::new();
+
 pub mod connecttool {
     tonic::include_proto!("connecttool");
 }
 
+mod mock;
+mod steam_id;
+mod steam_install;
+mod vdf;
+
 use connecttool::connect_tool_service_client::ConnectToolServiceClient;
 use connecttool::*;
 
 #[cfg(windows)]
 use tokio_util::compat::FuturesAsyncReadCompatExt;
 
+// ============== Logging ==============
+
+type LogFilterHandle = tracing_subscriber::reload::Handle<
+    tracing_subscriber::EnvFilter,
+    tracing_subscriber::Registry,
+>;
+
+// Handle for changing the log filter at runtime
+static LOG_FILTER: OnceCell<LogFilterHandle> = OnceCell::new();
+
+// Keeps the background log writer alive, pending lines are flushed when it is dropped
+static LOG_GUARD: OnceCell<tracing_appender::non_blocking::WorkerGuard> = OnceCell::new();
+
+const DEFAULT_LOG_LEVEL: &str = "info";
+
+/// Log to stdout and to daily-rotated files in the given directory
+fn init_logging(log_dir: Option<PathBuf>) {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let (filter, handle) =
+        tracing_subscriber::reload::Layer::new(tracing_subscriber::EnvFilter::new(DEFAULT_LOG_LEVEL));
+
+    // Without a log directory we still log to stdout
+    let file_layer = log_dir.and_then(|dir| {
+        let appender = tracing_appender::rolling::RollingFileAppender::builder()
+            .rotation(tracing_appender::rolling::Rotation::DAILY)
+            .filename_prefix("connect-tool-gui")
+            .filename_suffix("log")
+            .max_log_files(7)
+            .build(dir)
+            .ok()?;
+        let (writer, guard) = tracing_appender::non_blocking(appender);
+        let _ = LOG_GUARD.set(guard);
+        Some(tracing_subscriber::fmt::layer().with_writer(writer).with_ansi(false))
+    });
+
+    let initialized = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(file_layer)
+        .try_init();
+    if initialized.is_ok() {
+        let _ = LOG_FILTER.set(handle);
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn set_log_level(level: String) -> Result<(), CommandError> {
+    let filter = tracing_subscriber::EnvFilter::try_new(level.trim())
+        .map_err(|e| format!("Invalid log level \"{}\": {}", level, e))?;
+    LOG_FILTER
+        .get()
+        .ok_or("Logging is not initialized")?
+        .reload(filter)
+        .map_err(|e| format!("Failed to set log level: {}", e))?;
+    tracing::info!(level = %level.trim(), "log level changed");
+    Ok(())
+}
+
+// ============== End Logging ==============
+
+// ============== Persisted State ==============
+
+// App directories, resolved once during Tauri setup
+static APP_CONFIG_DIR: OnceCell<PathBuf> = OnceCell::new();
+static APP_DATA_DIR: OnceCell<PathBuf> = OnceCell::new();
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Everything the app persists between sessions, stored as one JSON file
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct Settings {
+    socket_path: Option<String>,
+    tcp_target: Option<TcpTarget>,
+    tls_config: Option<TlsConfig>,
+    connection_timeouts: Option<ConnectionTimeouts>,
+    steam_path_override: Option<PathBuf>,
+    core_path_override: Option<PathBuf>,
+    core_log_path_override: Option<PathBuf>,
+    last_lobby: Option<String>,
+    firewall_snapshot: Option<FirewallSnapshot>,
+    profiles: ProfilesState,
+    exit_behavior: ExitBehavior,
+    core_console_visible: bool,
+}
+
+// In-memory copy of settings.json, written through on every change
+static SETTINGS: Lazy<Mutex<Settings>> = Lazy::new(|| Mutex::new(Settings::default()));
+
+// Set when settings.json could not be parsed nor backed up, saving would destroy it
+static SETTINGS_WRITE_BLOCKED: AtomicBool = AtomicBool::new(false);
+
+/// Path of settings.json inside the app config directory
+fn settings_file() -> Option<PathBuf> {
+    APP_CONFIG_DIR.get().map(|dir| dir.join(SETTINGS_FILE))
+}
+
+/// Read a value from the persisted settings
+fn read_settings<T>(read: impl FnOnce(&Settings) -> T) -> T {
+    read(&SETTINGS.lock().unwrap())
+}
+
+/// Change the persisted settings, keeping the in-memory copy unchanged if saving fails
+fn modify_settings(modify: impl FnOnce(&mut Settings)) -> Result<(), String> {
+    try_modify_settings(|settings| {
+        modify(settings);
+        Ok(())
+    })
+}
+
+/// Change the persisted settings unless the change is rejected, checking and saving under one lock
+fn try_modify_settings<T>(modify: impl FnOnce(&mut Settings) -> Result<T, String>) -> Result<T, String> {
+    let mut settings = SETTINGS.lock().unwrap();
+    let mut updated = settings.clone();
+    let result = modify(&mut updated)?;
+    write_settings(&updated)?;
+    *settings = updated;
+    Ok(result)
+}
+
+/// Write settings.json atomically, so a crash mid-write can't leave a truncated file
+fn write_settings(settings: &Settings) -> Result<(), String> {
+    let path = settings_file().ok_or("App config directory is not available")?;
+    if SETTINGS_WRITE_BLOCKED.load(Ordering::SeqCst) {
+        return Err(format!(
+            "{} could not be read or backed up, refusing to overwrite it",
+            path.display()
+        ));
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create app config directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    let temp = path.with_extension("json.tmp");
+    std::fs::write(&temp, content).map_err(|e| format!("Failed to write {}: {}", temp.display(), e))?;
+    std::fs::rename(&temp, &path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+/// Load settings.json, migrating the per-feature files of older versions on first run
+fn load_settings() {
+    let Some(path) = settings_file() else {
+        return;
+    };
+
+    let settings: Settings = match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            // The next save replaces the file, so keep a copy of what the user had
+            let backup = path.with_extension("json.bak");
+            match std::fs::copy(&path, &backup) {
+                Ok(_) => tracing::warn!(
+                    error = %e,
+                    path = %path.display(),
+                    backup = %backup.display(),
+                    "unreadable settings file backed up, using defaults"
+                ),
+                Err(copy_err) => {
+                    tracing::error!(
+                        error = %e,
+                        backup_error = %copy_err,
+                        path = %path.display(),
+                        "unreadable settings file could not be backed up, changes will not be saved"
+                    );
+                    SETTINGS_WRITE_BLOCKED.store(true, Ordering::SeqCst);
+                }
+            }
+            Settings::default()
+        }),
+        Err(_) => migrate_legacy_settings(),
+    };
+
+    if settings.tls_config.as_ref().is_some_and(|tls| tls.legacy_client_identity.is_some()) {
+        tracing::warn!("client key is no longer kept in settings, set the client key path again for mutual TLS");
+    }
+    *SETTINGS.lock().unwrap() = settings;
+}
+
+/// Build settings from the separate JSON files older versions kept in the app data directory
+fn migrate_legacy_settings() -> Settings {
+    fn legacy<T: serde::de::DeserializeOwned>(name: &str) -> Option<T> {
+        let content = std::fs::read_to_string(APP_DATA_DIR.get()?.join(name)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+    const LEGACY_FILES: &[&str] = &[
+        "socket_path.json",
+        "tcp_target.json",
+        "tls_config.json",
+        "steam_path_override.json",
+        "core_path_override.json",
+        "core_log_path_override.json",
+        "last_lobby.json",
+        "firewall_snapshot.json",
+        "profiles.json",
+    ];
+
+    let settings = Settings {
+        socket_path: legacy("socket_path.json"),
+        tcp_target: legacy("tcp_target.json"),
+        tls_config: legacy("tls_config.json"),
+        connection_timeouts: None,
+        steam_path_override: legacy("steam_path_override.json"),
+        core_path_override: legacy("core_path_override.json"),
+        core_log_path_override: legacy("core_log_path_override.json"),
+        last_lobby: legacy("last_lobby.json"),
+        firewall_snapshot: legacy("firewall_snapshot.json"),
+        profiles: legacy("profiles.json").unwrap_or_default(),
+        exit_behavior: ExitBehavior::default(),
+        core_console_visible: false,
+    };
+
+    let Some(data_dir) = APP_DATA_DIR.get() else {
+        return settings;
+    };
+    let legacy_paths: Vec<PathBuf> = LEGACY_FILES
+        .iter()
+        .map(|name| data_dir.join(name))
+        .filter(|path| path.exists())
+        .collect();
+    if legacy_paths.is_empty() {
+        return settings;
+    }
+
+    // Only drop the old files once their contents are safely in settings.json
+    match write_settings(&settings) {
+        Ok(()) => {
+            for path in &legacy_paths {
+                let _ = std::fs::remove_file(path);
+            }
+            tracing::info!(files = legacy_paths.len(), "migrated legacy settings files");
+        }
+        Err(e) => tracing::warn!(error = %e, "failed to migrate legacy settings files"),
+    }
+    settings
+}
+
+/// Settings as shown to the frontend, TLS key material is left out
+#[derive(serde::Serialize)]
+pub struct SettingsResponse {
+    pub settings_path: Option<String>,
+    pub connection_mode: ConnectionMode,
+    pub tls_enabled: bool,
+    pub connection_timeouts: ConnectionTimeouts,
+    pub steam_path_override: Option<String>,
+    pub core_path_override: Option<String>,
+    pub core_log_path_override: Option<String>,
+    pub exit_behavior: ExitBehavior,
+    pub core_console_visible: bool,
+}
+
+/// Fields to change with update_settings, omitted fields are left as they are
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct SettingsPatch {
+    pub connection_mode: Option<ConnectionMode>,
+    pub connection_timeouts: Option<ConnectionTimeouts>,
+    /// An empty string clears the override
+    pub steam_path_override: Option<String>,
+    pub core_path_override: Option<String>,
+    pub core_log_path_override: Option<String>,
+    pub exit_behavior: Option<ExitBehavior>,
+    pub core_console_visible: Option<bool>,
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_settings() -> Result<SettingsResponse, CommandError> {
+    let path_string = |path: &Option<PathBuf>| path.as_ref().map(|p| p.to_string_lossy().to_string());
+    // Resolved before taking the settings lock, these helpers may read settings themselves
+    let settings_path = settings_file().map(|path| path.to_string_lossy().to_string());
+    let connection_mode = connection_mode();
+    let connection_timeouts = connection_timeouts();
+    Ok(read_settings(|settings| SettingsResponse {
+        settings_path,
+        connection_mode,
+        tls_enabled: settings.tls_config.is_some(),
+        connection_timeouts,
+        steam_path_override: path_string(&settings.steam_path_override),
+        core_path_override: path_string(&settings.core_path_override),
+        core_log_path_override: path_string(&settings.core_log_path_override),
+        exit_behavior: settings.exit_behavior,
+        core_console_visible: settings.core_console_visible,
+    }))
+}
+
+/// Apply a partial settings update. Each field goes through the same validation as its
+/// dedicated command, fields are applied in order and the first invalid one stops the update.
+#[tauri::command]
+#[tracing::instrument]
+async fn update_settings(patch: SettingsPatch) -> Result<SettingsResponse, CommandError> {
+    ensure_writable()?;
+    if let Some(mode) = patch.connection_mode {
+        set_connection_mode(mode).await?;
+    }
+    if let Some(timeouts) = patch.connection_timeouts {
+        set_connection_timeouts(timeouts.connect_timeout_ms, timeouts.request_timeout_ms).await?;
+    }
+    if let Some(path) = patch.steam_path_override {
+        if path.trim().is_empty() {
+            clear_steam_path_override().await?;
+        } else {
+            set_steam_path_override(path).await?;
+        }
+    }
+    if let Some(path) = patch.core_path_override {
+        if path.trim().is_empty() {
+            clear_core_path_override().await?;
+        } else {
+            set_core_path_override(path).await?;
+        }
+    }
+    if let Some(path) = patch.core_log_path_override {
+        if path.trim().is_empty() {
+            clear_core_log_path_override().await?;
+        } else {
+            set_core_log_path_override(path).await?;
+        }
+    }
+    if let Some(mode) = patch.exit_behavior {
+        set_exit_behavior(mode).await?;
+    }
+    if let Some(visible) = patch.core_console_visible {
+        set_core_console_visible(visible).await?;
+    }
+    get_settings().await
+}
+
+/// Current time as seconds since the Unix epoch
+fn unix_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// ============== End Persisted State ==============
+
 // ============== Steam Path Finding ==============
 
 /// Response structure for find_steam_path command
@@ -27,14 +403,32 @@ pub struct FindSteamPathResponse {
     pub found: bool,
     pub steam_path: Option<String>,
     pub steam_exe_path: Option<String>,
+    /// Which probe located Steam, e.g. "HKCU" or "default:D:\Steam"
+    pub source: Option<String>,
+    /// Running on a Steam Deck, where Gaming Mode changes how Steam is launched
+    pub is_steam_deck: bool,
     pub message: String,
 }
 
+/// How Steam or ConnectToolCore was stopped
+#[derive(Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GracefulShutdownResult {
+    NotRunning,
+    Graceful,
+    Forced,
+}
+
 /// Response structure for restart_steam_china command
 #[derive(serde::Serialize)]
 pub struct RestartSteamChinaResponse {
     pub success: bool,
     pub message: String,
+    pub shutdown_result: Option<GracefulShutdownResult>,
+    /// Steam processes that were stopped before relaunching
+    pub stopped_pids: Vec<u32>,
+    /// Whether cancel_steam_restart aborted the restart
+    pub cancelled: bool,
 }
 
 /// Response structure for get_steam_status command
@@ -42,210 +436,268 @@ pub struct RestartSteamChinaResponse {
 pub struct GetSteamStatusResponse {
     pub is_running: bool,
     pub process_id: Option<u32>,
+    /// Every Steam process found, including launchers and wrappers
+    pub process_ids: Vec<u32>,
+    /// Whether Steam was launched with -steamchina, None if the command line is unreadable
+    pub china_mode: Option<bool>,
+}
+
+/// A Steam library folder from libraryfolders.vdf
+#[derive(serde::Serialize)]
+pub struct SteamLibrary {
+    pub path: String,
+    pub label: Option<String>,
+}
+
+/// An account that has logged in to Steam on this machine, from loginusers.vdf
+#[derive(serde::Serialize)]
+pub struct SteamAccount {
+    pub steam_id64: String,
+    pub account_name: String,
+    pub persona_name: Option<String>,
+    /// The account Steam logs in with by default
+    pub most_recent: bool,
 }
 
 /// Find Steam installation path on Windows
 #[cfg(windows)]
-fn find_steam_path_windows() -> Option<PathBuf> {
+fn find_steam_path_windows() -> Option<(PathBuf, String)> {
+    use steam_install::RegistryRoot;
     use winreg::enums::*;
     use winreg::RegKey;
 
-    // Try HKEY_CURRENT_USER first
-    if let Ok(hkcu) = RegKey::predef(HKEY_CURRENT_USER).open_subkey("Software\\Valve\\Steam") {
-        if let Ok(path) = hkcu.get_value::<String, _>("SteamPath") {
-            let steam_path = PathBuf::from(&path);
-            if steam_path.exists() {
-                return Some(steam_path);
-            }
-        }
-    }
-
-    // Try HKEY_LOCAL_MACHINE (32-bit)
-    if let Ok(hklm) = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey("SOFTWARE\\Valve\\Steam") {
-        if let Ok(path) = hklm.get_value::<String, _>("InstallPath") {
-            let steam_path = PathBuf::from(&path);
-            if steam_path.exists() {
-                return Some(steam_path);
-            }
-        }
-    }
-
-    // Try HKEY_LOCAL_MACHINE (64-bit - WOW6432Node)
-    if let Ok(hklm) = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey("SOFTWARE\\WOW6432Node\\Valve\\Steam") {
-        if let Ok(path) = hklm.get_value::<String, _>("InstallPath") {
-            let steam_path = PathBuf::from(&path);
-            if steam_path.exists() {
-                return Some(steam_path);
-            }
-        }
-    }
-
-    // Fallback: check default locations
-    let default_paths = vec![
-        "C:\\Program Files (x86)\\Steam",
-        "C:\\Program Files\\Steam",
-        "D:\\Steam",
-        "D:\\Program Files (x86)\\Steam",
-    ];
+    let read_registry = |value: &steam_install::RegistryValue| {
+        let root = match value.root {
+            RegistryRoot::CurrentUser => HKEY_CURRENT_USER,
+            RegistryRoot::LocalMachine => HKEY_LOCAL_MACHINE,
+        };
+        RegKey::predef(root)
+            .open_subkey(value.key)
+            .ok()?
+            .get_value::<String, _>(value.name)
+            .ok()
+    };
 
-    for path in default_paths {
-        let steam_path = PathBuf::from(path);
-        if steam_path.exists() && steam_path.join("steam.exe").exists() {
-            return Some(steam_path);
-        }
-    }
+    // Default locations are resolved from the environment so non-C: system drives work.
+    // ProgramW6432 points at the native Program Files even from a 32-bit or emulated process on ARM64.
+    let program_files: Vec<PathBuf> = ["ProgramFiles(x86)", "ProgramFiles", "ProgramW6432"]
+        .into_iter()
+        .filter_map(std::env::var_os)
+        .map(PathBuf::from)
+        .collect();
 
-    None
+    steam_install::find_windows(read_registry, &program_files, &steam_install::RealFileSystem)
 }
 
 /// Find Steam installation path on macOS
 #[cfg(target_os = "macos")]
-fn find_steam_path_macos() -> Option<PathBuf> {
-    // Try mdfind first
-    if let Ok(output) = Command::new("mdfind")
-        .args(&["kMDItemCFBundleIdentifier", "=", "com.valvesoftware.steam"])
+fn find_steam_path_macos() -> Option<(PathBuf, String)> {
+    let mdfind_results: Vec<PathBuf> = Command::new("mdfind")
+        .args(["kMDItemCFBundleIdentifier", "=", "com.valvesoftware.steam"])
         .output()
-    {
-        if output.status.success() {
-            let path_str = String::from_utf8_lossy(&output.stdout);
-            for line in path_str.lines() {
-                let steam_path = PathBuf::from(line.trim());
-                if steam_path.exists() {
-                    return Some(steam_path);
-                }
-            }
-        }
-    }
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(|line| PathBuf::from(line.trim()))
+                .collect()
+        })
+        .unwrap_or_default();
 
-    // Check default location
-    let default_path = PathBuf::from("/Applications/Steam.app");
-    if default_path.exists() {
-        return Some(default_path);
-    }
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    steam_install::find_macos(&mdfind_results, home.as_deref(), &steam_install::RealFileSystem)
+}
 
-    // Check user's home directory
-    if let Ok(home) = std::env::var("HOME") {
-        let user_path = PathBuf::from(home).join("Applications/Steam.app");
-        if user_path.exists() {
-            return Some(user_path);
-        }
-    }
+/// Find Steam installation path on Linux
+#[cfg(target_os = "linux")]
+fn find_steam_path_linux() -> Option<(PathBuf, String)> {
+    let which_steam = Command::new("which")
+        .arg("steam")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()));
 
-    None
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    steam_install::find_linux(is_steam_deck(), home.as_deref(), which_steam, &steam_install::RealFileSystem)
 }
 
-/// Find Steam installation path on Linux
+/// Get Steam executable path
+fn get_steam_exe_path(steam_path: &Path) -> Option<PathBuf> {
+    steam_install::exe_path(steam_path, &steam_install::RealFileSystem)
+}
+
+/// Detect a Steam Deck, or another SteamOS device, from the OS release and DMI board info
 #[cfg(target_os = "linux")]
-fn find_steam_path_linux() -> Option<PathBuf> {
-    // Try which steam
-    if let Ok(output) = Command::new("which").arg("steam").output() {
-        if output.status.success() {
-            let path_str = String::from_utf8_lossy(&output.stdout);
-            let steam_path = PathBuf::from(path_str.trim());
-            if steam_path.exists() {
-                // Get the actual Steam installation directory
-                if let Ok(real_path) = std::fs::read_link(&steam_path) {
-                    if let Some(parent) = real_path.parent() {
-                        return Some(parent.to_path_buf());
-                    }
-                }
-                return Some(steam_path);
-            }
-        }
+fn is_steam_deck() -> bool {
+    let os_release = std::fs::read_to_string("/etc/os-release").unwrap_or_default();
+    if os_release.lines().any(|line| line.trim() == "ID=steamos") {
+        return true;
     }
 
-    // Check common paths
-    if let Ok(home) = std::env::var("HOME") {
-        let paths = vec![
-            PathBuf::from(&home).join(".steam/steam"),
-            PathBuf::from(&home).join(".steam"),
-            PathBuf::from(&home).join(".local/share/Steam"),
-            PathBuf::from("/usr/share/steam"),
-            PathBuf::from("/usr/lib/steam"),
-        ];
-
-        for path in paths {
-            if path.exists() {
-                return Some(path);
-            }
-        }
+    let read_dmi = |name: &str| {
+        std::fs::read_to_string(format!("/sys/devices/virtual/dmi/id/{}", name))
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    };
+    // Jupiter is the LCD model, Galileo the OLED one
+    if read_dmi("board_vendor") == "Valve"
+        && matches!(read_dmi("board_name").as_str(), "Jupiter" | "Galileo")
+    {
+        return true;
     }
 
-    None
+    // Last resort: the Deck's default user
+    std::env::var("HOME").is_ok_and(|home| home == "/home/deck") && Path::new("/home/deck/.steam").exists()
 }
 
-/// Get Steam executable path
-fn get_steam_exe_path(steam_path: &PathBuf) -> Option<PathBuf> {
-    #[cfg(windows)]
-    {
-        let exe = steam_path.join("steam.exe");
-        if exe.exists() {
-            return Some(exe);
+#[cfg(not(target_os = "linux"))]
+fn is_steam_deck() -> bool {
+    false
+}
+
+/// Cross-platform Steam path finder, also reporting which probe matched
+fn find_steam_path_with_source() -> Option<(PathBuf, String)> {
+    // A user-specified Steam directory takes precedence over auto-detection
+    if let Some(path) = read_settings(|s| s.steam_path_override.clone()) {
+        // Fall back to auto-detection if the override was moved or deleted
+        if get_steam_exe_path(&path).is_some() {
+            return Some((path, "override".to_string()));
         }
     }
 
+    #[cfg(windows)]
+    return find_steam_path_windows();
+
+    #[cfg(target_os = "macos")]
+    return find_steam_path_macos();
+
+    #[cfg(target_os = "linux")]
+    return find_steam_path_linux();
+
+    #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+    return None;
+}
+
+/// Cross-platform Steam path finder
+fn find_steam_path() -> Option<PathBuf> {
+    find_steam_path_with_source().map(|(path, _)| path)
+}
+
+/// Directory holding Steam's data (steamapps, config), which may differ from the install path
+fn get_steam_data_dir(steam_path: &Path) -> PathBuf {
     #[cfg(target_os = "macos")]
     {
-        let exe = steam_path.join("Contents/MacOS/steam_osx");
-        if exe.exists() {
-            return Some(exe);
-        }
-        // Alternative path
-        let exe2 = steam_path.join("Contents/MacOS/Steam");
-        if exe2.exists() {
-            return Some(exe2);
+        // steam_path is the app bundle, data lives in Application Support
+        if let Ok(home) = std::env::var("HOME") {
+            let data_dir = PathBuf::from(home).join("Library/Application Support/Steam");
+            if data_dir.exists() {
+                return data_dir;
+            }
         }
     }
 
     #[cfg(target_os = "linux")]
     {
-        // Check if steam_path is the executable itself
-        if steam_path.is_file() {
-            return Some(steam_path.clone());
+        // steam_path may be the launcher binary rather than the data directory
+        if !steam_path.join("steamapps").exists() {
+            if let Ok(home) = std::env::var("HOME") {
+                let data_dir = PathBuf::from(home).join(".steam/steam");
+                if data_dir.exists() {
+                    return data_dir;
+                }
+            }
         }
-        let exe = steam_path.join("steam");
-        if exe.exists() {
-            return Some(exe);
+    }
+
+    steam_path.to_path_buf()
+}
+
+/// Extract library folders from the contents of libraryfolders.vdf
+fn parse_library_folders(content: &str) -> Result<Vec<SteamLibrary>, String> {
+    let root = vdf::parse(content)?;
+    let folders = root
+        .get("libraryfolders")
+        .ok_or("libraryfolders.vdf has no libraryfolders block")?;
+
+    let mut libraries = Vec::new();
+    for (key, value) in folders.entries() {
+        // Libraries are keyed by index, other keys hold metadata such as ContentStatsID
+        if key.parse::<u32>().is_err() {
+            continue;
         }
-        let exe2 = steam_path.join("steam.sh");
-        if exe2.exists() {
-            return Some(exe2);
+
+        match value {
+            // Old format: "1" "D:\\SteamLibrary"
+            vdf::VdfValue::String(path) => libraries.push(SteamLibrary {
+                path: path.clone(),
+                label: None,
+            }),
+            // New format: "0" { "path" "..." "label" "..." }
+            vdf::VdfValue::Object(_) => {
+                if let Some(path) = value.get_str("path") {
+                    libraries.push(SteamLibrary {
+                        path: path.to_string(),
+                        label: value
+                            .get_str("label")
+                            .filter(|l| !l.is_empty())
+                            .map(str::to_string),
+                    });
+                }
+            }
         }
     }
 
-    None
+    Ok(libraries)
 }
 
-/// Cross-platform Steam path finder
-fn find_steam_path() -> Option<PathBuf> {
-    #[cfg(windows)]
-    return find_steam_path_windows();
-
-    #[cfg(target_os = "macos")]
-    return find_steam_path_macos();
+/// Parse the accounts from the contents of loginusers.vdf
+fn parse_login_users(content: &str) -> Result<Vec<SteamAccount>, String> {
+    let root = vdf::parse(content)?;
+    let users = root.get("users").ok_or("loginusers.vdf has no users block")?;
 
-    #[cfg(target_os = "linux")]
-    return find_steam_path_linux();
+    let mut accounts = Vec::new();
+    for (key, user) in users.entries() {
+        // Users are keyed by SteamID64
+        if key.parse::<u64>().is_err() {
+            continue;
+        }
+        let Some(account_name) = user.get_str("AccountName") else {
+            continue;
+        };
+        accounts.push(SteamAccount {
+            steam_id64: key.clone(),
+            account_name: account_name.to_string(),
+            persona_name: user
+                .get_str("PersonaName")
+                .filter(|name| !name.is_empty())
+                .map(str::to_string),
+            most_recent: user.get_str("MostRecent") == Some("1"),
+        });
+    }
 
-    #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
-    return None;
+    Ok(accounts)
 }
 
-/// Check if Steam is running on Windows
+/// Find the PIDs of all running Steam processes on Windows via tasklist
 #[cfg(windows)]
-fn is_steam_running() -> Option<u32> {
+fn find_steam_pids_tasklist() -> Vec<u32> {
     use std::os::windows::process::CommandExt;
     const CREATE_NO_WINDOW: u32 = 0x08000000;
 
-    let output = Command::new("tasklist")
-        .args(&["/FI", "IMAGENAME eq steam.exe", "/FO", "CSV", "/NH"])
+    let Ok(output) = Command::new("tasklist")
+        .args(["/FI", "IMAGENAME eq steam.exe", "/FO", "CSV", "/NH"])
         .creation_flags(CREATE_NO_WINDOW)
         .output()
-        .ok()?;
+    else {
+        return Vec::new();
+    };
 
     let output_str = String::from_utf8_lossy(&output.stdout);
     
-    // Parse CSV output to get PID
+    // Parse CSV output to get PIDs
+    let mut pids = Vec::new();
     for line in output_str.lines() {
         if line.contains("steam.exe") {
             // Format: "steam.exe","PID","Session Name","Session#","Mem Usage"
@@ -254,73 +706,314 @@ fn is_steam_running() -> Option<u32> {
                 // Remove quotes and parse PID
                 let pid_str = parts[1].trim_matches('"');
                 if let Ok(pid) = pid_str.parse::<u32>() {
-                    return Some(pid);
+                    pids.push(pid);
                 }
             }
         }
     }
-    None
+    pids
 }
 
-/// Check if Steam is running on Unix
-#[cfg(unix)]
-fn is_steam_running() -> Option<u32> {
-    let output = Command::new("pgrep")
-        .args(&["-x", "steam"])
+/// Find the PIDs of all running Steam processes on Windows via WMI
+#[cfg(windows)]
+fn find_steam_pids_wmi() -> Vec<u32> {
+    let Ok(output) = run_powershell(
+        "Get-CimInstance Win32_Process -Filter \"Name='steam.exe'\" | Select-Object -ExpandProperty ProcessId",
+    ) else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().parse::<u32>().ok())
+        .collect()
+}
+
+/// Find the PIDs of all running Steam processes on Windows
+#[cfg(windows)]
+fn find_steam_pids() -> Vec<u32> {
+    // tasklist is fast but its output can be localized or malformed, WMI is the robust fallback
+    let pids = find_steam_pids_tasklist();
+    if !pids.is_empty() {
+        return pids;
+    }
+    find_steam_pids_wmi()
+}
+
+// Flatpak application id of Steam
+#[cfg(target_os = "linux")]
+const STEAM_FLATPAK_ID: &str = "com.valvesoftware.Steam";
+
+/// Check whether the Flatpak build of Steam is running
+#[cfg(target_os = "linux")]
+fn is_steam_flatpak_running() -> bool {
+    Command::new("flatpak")
+        .args(["ps", "--columns=application"])
         .output()
-        .ok()?;
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .any(|line| line.trim() == STEAM_FLATPAK_ID)
+        })
+        .unwrap_or(false)
+}
 
-    if output.status.success() {
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        if let Ok(pid) = output_str.trim().lines().next()?.parse::<u32>() {
-            return Some(pid);
+/// Find the PIDs of all running Steam processes on Unix, the client binary first
+#[cfg(unix)]
+fn find_steam_pids() -> Vec<u32> {
+    // The client binary, the steam.sh launcher that wraps it, and the Snap build's processes
+    let mut patterns = vec![["-x", "steam"], ["-f", "steam\\.sh"]];
+    if Path::new("/snap/steam").exists() {
+        patterns.push(["-f", "^/snap/steam/"]);
+    }
+
+    let own_pid = std::process::id();
+    let mut pids = Vec::new();
+    for args in patterns {
+        let Ok(output) = Command::new("pgrep").args(args).output() else {
+            continue;
+        };
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            if let Ok(pid) = line.trim().parse::<u32>() {
+                if pid != own_pid && !pids.contains(&pid) {
+                    pids.push(pid);
+                }
+            }
         }
     }
-    None
+    pids
+}
+
+/// Check if Steam is running, returning the PID of the main process
+fn is_steam_running() -> Option<u32> {
+    find_steam_pids().first().copied()
 }
 
-/// Kill Steam process on Windows
+/// Read the command line of the running Steam process on Windows
 #[cfg(windows)]
-fn kill_steam_process() -> Result<(), String> {
+fn get_steam_command_line(pid: u32) -> Option<String> {
     use std::os::windows::process::CommandExt;
     const CREATE_NO_WINDOW: u32 = 0x08000000;
 
-    let output = Command::new("taskkill")
-        .args(&["/IM", "steam.exe", "/F"])
+    let cmd = format!(
+        "Get-CimInstance Win32_Process -Filter \"ProcessId={}\" | Select-Object -ExpandProperty CommandLine",
+        pid
+    );
+    let output = Command::new("powershell")
+        .args(["-Command", &cmd])
         .creation_flags(CREATE_NO_WINDOW)
         .output()
-        .map_err(|e| format!("Failed to execute taskkill: {}", e))?;
+        .ok()?;
 
-    if output.status.success() {
-        // Wait a bit for Steam to fully close
-        std::thread::sleep(std::time::Duration::from_secs(2));
-        Ok(())
-    } else {
-        let error = String::from_utf8_lossy(&output.stderr);
-        Err(format!("Failed to kill Steam: {}", error))
+    if !output.status.success() {
+        return None;
     }
+
+    // CommandLine is empty when we lack permission to read it
+    let command_line = String::from_utf8_lossy(&output.stdout);
+    let first = command_line.lines().map(str::trim).find(|l| !l.is_empty())?;
+    Some(first.to_string())
 }
 
-/// Kill Steam process on Unix
-#[cfg(unix)]
-fn kill_steam_process() -> Result<(), String> {
-    let output = Command::new("pkill")
-        .args(&["-x", "steam"])
+/// Read the command line of the running Steam process on Linux
+#[cfg(target_os = "linux")]
+fn get_steam_command_line(pid: u32) -> Option<String> {
+    let raw = std::fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    if raw.is_empty() {
+        return None;
+    }
+    // Arguments are NUL separated
+    Some(
+        raw.split(|b| *b == 0)
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// Read the command line of the running Steam process on macOS
+#[cfg(target_os = "macos")]
+fn get_steam_command_line(pid: u32) -> Option<String> {
+    let output = Command::new("ps")
+        .args(["-o", "args=", "-p", &pid.to_string()])
         .output()
-        .map_err(|e| format!("Failed to execute pkill: {}", e))?;
+        .ok()?;
 
-    if output.status.success() || output.status.code() == Some(1) {
-        // Wait a bit for Steam to fully close
-        std::thread::sleep(std::time::Duration::from_secs(2));
-        Ok(())
+    let command_line = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && !command_line.is_empty() {
+        Some(command_line)
     } else {
-        let error = String::from_utf8_lossy(&output.stderr);
-        Err(format!("Failed to kill Steam: {}", error))
+        None
     }
 }
 
-/// Start Steam with -steamchina argument
-fn start_steam_china(steam_exe_path: &PathBuf) -> Result<(), String> {
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn get_steam_command_line(_pid: u32) -> Option<String> {
+    None
+}
+
+/// Check whether Steam's command line contains the -steamchina flag
+fn steam_china_mode(pid: u32) -> Option<bool> {
+    let command_line = get_steam_command_line(pid)?;
+    Some(
+        command_line
+            .split_whitespace()
+            .any(|arg| arg.trim_matches('"').eq_ignore_ascii_case("-steamchina")),
+    )
+}
+
+/// Kill all Steam processes on Windows, returning the PIDs that were running
+#[cfg(windows)]
+fn kill_steam_process() -> Result<Vec<u32>, String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let pids = find_steam_pids();
+    let output = Command::new("taskkill")
+        .args(["/IM", "steam.exe", "/F"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to execute taskkill: {}", e))?;
+
+    if output.status.success() {
+        wait_for_steam_killed()?;
+        Ok(pids)
+    } else {
+        let error = String::from_utf8_lossy(&output.stderr);
+        Err(format!("Failed to kill Steam: {}", error))
+    }
+}
+
+/// Get the process group of a process on Unix
+#[cfg(unix)]
+fn get_process_group(pid: u32) -> Option<u32> {
+    let output = Command::new("ps")
+        .args(["-o", "pgid=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// Kill all Steam processes on Unix, returning the PIDs that were running
+#[cfg(unix)]
+fn kill_steam_process() -> Result<Vec<u32>, String> {
+    let pids = find_steam_pids();
+
+    // The Flatpak build runs inside a bwrap sandbox, stop the whole instance
+    #[cfg(target_os = "linux")]
+    if is_steam_flatpak_running() {
+        let _ = Command::new("flatpak").args(["kill", STEAM_FLATPAK_ID]).output();
+    }
+
+    // Kill whole process groups so helpers like steamwebhelper go too, but never our own group,
+    // which a Steam started by an older version of the app may share
+    let own_group = get_process_group(std::process::id());
+    let mut targets: Vec<String> = Vec::new();
+    for &pid in &pids {
+        let target = match get_process_group(pid) {
+            Some(group) if Some(group) != own_group && group > 1 => format!("-{}", group),
+            _ => pid.to_string(),
+        };
+        if !targets.contains(&target) {
+            targets.push(target);
+        }
+    }
+
+    if !targets.is_empty() {
+        let output = Command::new("kill")
+            .arg("-KILL")
+            .arg("--")
+            .args(&targets)
+            .output()
+            .map_err(|e| format!("Failed to execute kill: {}", e))?;
+
+        // Processes that already exited make kill fail, only complain if Steam survived
+        if !output.status.success() && !find_steam_pids().is_empty() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Failed to kill Steam: {}", error));
+        }
+    }
+
+    wait_for_steam_killed()?;
+    Ok(pids)
+}
+
+/// Ask Steam to exit on its own via the -shutdown argument
+fn request_steam_shutdown(steam_exe_path: &Path) -> Result<(), String> {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+        Command::new(steam_exe_path)
+            .arg("-shutdown")
+            .creation_flags(CREATE_NO_WINDOW)
+            .spawn()
+            .map_err(|e| format!("Failed to request Steam shutdown: {}", e))?;
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        Command::new(steam_exe_path)
+            .arg("-shutdown")
+            .spawn()
+            .map_err(|e| format!("Failed to request Steam shutdown: {}", e))?;
+        Ok(())
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        let _ = steam_exe_path;
+        Err("Graceful Steam shutdown is not supported on this platform".to_string())
+    }
+}
+
+/// Poll until Steam is no longer running, returning false on timeout
+fn wait_for_steam_exit(timeout: Duration) -> bool {
+    let deadline = std::time::Instant::now() + timeout;
+    while is_steam_running().is_some() {
+        if std::time::Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+    true
+}
+
+/// Wait for killed Steam processes to disappear, so a relaunch can't collide with them
+fn wait_for_steam_killed() -> Result<(), String> {
+    if wait_for_steam_exit(Duration::from_secs(15)) {
+        Ok(())
+    } else {
+        Err("Steam did not exit in time".to_string())
+    }
+}
+
+/// Stop Steam, preferring a graceful shutdown and falling back to a force kill.
+/// Also returns the PIDs of the Steam processes that were stopped.
+fn stop_steam(steam_exe_path: &Path) -> Result<(GracefulShutdownResult, Vec<u32>), String> {
+    let pids = find_steam_pids();
+    if pids.is_empty() {
+        return Ok((GracefulShutdownResult::NotRunning, pids));
+    }
+
+    if request_steam_shutdown(steam_exe_path).is_ok() && wait_for_steam_exit(Duration::from_secs(10)) {
+        return Ok((GracefulShutdownResult::Graceful, pids));
+    }
+
+    let mut killed = kill_steam_process()?;
+    // Include processes that only showed up while waiting for the graceful shutdown
+    for pid in pids {
+        if !killed.contains(&pid) {
+            killed.push(pid);
+        }
+    }
+    Ok((GracefulShutdownResult::Forced, killed))
+}
+
+/// Launch Steam with the given arguments
+fn spawn_steam(steam_exe_path: &Path, args: &[&str]) -> std::io::Result<()> {
     #[cfg(windows)]
     {
         use std::os::windows::process::CommandExt;
@@ -328,31 +1021,74 @@ fn start_steam_china(steam_exe_path: &PathBuf) -> Result<(), String> {
         const CREATE_NO_WINDOW: u32 = 0x08000000;
 
         Command::new(steam_exe_path)
-            .arg("-steamchina")
+            .args(args)
             .creation_flags(DETACHED_PROCESS | CREATE_NO_WINDOW)
-            .spawn()
-            .map_err(|e| format!("Failed to start Steam: {}", e))?;
+            .spawn()?;
     }
 
     #[cfg(target_os = "macos")]
     {
-        Command::new("open")
-            .args(&["-a", steam_exe_path.to_str().unwrap_or("Steam"), "--args", "-steamchina"])
-            .spawn()
-            .map_err(|e| format!("Failed to start Steam: {}", e))?;
+        let mut command = Command::new("open");
+        command.args(["-a", steam_exe_path.to_str().unwrap_or("Steam")]);
+        if !args.is_empty() {
+            command.arg("--args").args(args);
+        }
+        command.spawn()?;
     }
 
     #[cfg(target_os = "linux")]
     {
+        use std::os::unix::process::CommandExt;
+
+        // A separate process group lets kill_steam_process take Steam down without touching us
         Command::new(steam_exe_path)
-            .arg("-steamchina")
-            .spawn()
-            .map_err(|e| format!("Failed to start Steam: {}", e))?;
+            .args(args)
+            .process_group(0)
+            .spawn()?;
     }
 
     Ok(())
 }
 
+/// Whether a launch failed because the executable is still held open, e.g. by an antivirus scan
+/// or a Steam process that is slow to exit
+fn is_steam_exe_busy(err: &std::io::Error) -> bool {
+    // ERROR_SHARING_VIOLATION and ERROR_LOCK_VIOLATION
+    #[cfg(windows)]
+    const BUSY_CODES: &[i32] = &[32, 33];
+    // ETXTBSY
+    #[cfg(not(windows))]
+    const BUSY_CODES: &[i32] = &[26];
+
+    err.raw_os_error().is_some_and(|code| BUSY_CODES.contains(&code))
+}
+
+/// Start Steam with the given launch arguments, retrying while the executable is busy
+async fn start_steam(steam_exe_path: &Path, args: &[&str]) -> Result<(), String> {
+    const ATTEMPTS: u32 = 3;
+    const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+    let mut attempt = 1;
+    loop {
+        match spawn_steam(steam_exe_path, args) {
+            Ok(()) => return Ok(()),
+            Err(e) if is_steam_exe_busy(&e) => {
+                if attempt >= ATTEMPTS {
+                    return Err(format!(
+                        "Steam executable busy: {} is still in use by another process ({})",
+                        steam_exe_path.display(),
+                        e
+                    ));
+                }
+                tracing::warn!(attempt, error = %e, "Steam executable busy, retrying launch");
+            }
+            Err(e) => return Err(format!("Failed to start Steam: {}", e)),
+        }
+        tokio::time::sleep(RETRY_DELAY).await;
+        attempt += 1;
+    }
+}
+
 // ============== End Steam Path Finding ==============
 
 #[cfg(windows)]
@@ -397,563 +1133,5112 @@ impl futures::io::AsyncWrite for AsyncWindowsUds {
 #[cfg(windows)]
 async fn connect_uds(
     path: &str,
-) -> Result<
+) -> std::io::Result<
     TokioIo<impl tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static>,
-    String,
 > {
-    let stream = uds_windows::UnixStream::connect(path).map_err(|e| e.to_string())?;
-    stream.set_nonblocking(true).map_err(|e| e.to_string())?;
-    let stream = async_io::Async::new(stream).map_err(|e| e.to_string())?;
+    let stream = uds_windows::UnixStream::connect(path)?;
+    stream.set_nonblocking(true)?;
+    let stream = async_io::Async::new(stream)?;
     Ok(TokioIo::new(AsyncWindowsUds(stream).compat()))
 }
 
 #[cfg(unix)]
-async fn connect_uds(path: &str) -> Result<TokioIo<tokio::net::UnixStream>, String> {
-    let stream = tokio::net::UnixStream::connect(path)
-        .await
-        .map_err(|e| e.to_string())?;
+async fn connect_uds(path: &str) -> std::io::Result<TokioIo<tokio::net::UnixStream>> {
+    // Otherwise this surfaces as a bare ENOENT, indistinguishable from a missing socket
+    if let Some(dir) = missing_socket_dir(path) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("socket directory missing: {}", dir.display()),
+        ));
+    }
+    let stream = tokio::net::UnixStream::connect(path).await?;
     Ok(TokioIo::new(stream))
 }
 
-// Helper to get client
-async fn get_client() -> Result<ConnectToolServiceClient<tonic::transport::Channel>, String> {
-    // Determine socket path
+/// Parent directory of a socket path if it does not exist, bare file names have none
+fn missing_socket_dir(path: &str) -> Option<PathBuf> {
+    let parent = Path::new(path).parent()?;
+    (!parent.as_os_str().is_empty() && !parent.is_dir()).then(|| parent.to_path_buf())
+}
+
+/// Default socket path used by ConnectToolCore
+fn default_socket_path() -> &'static str {
     #[cfg(windows)]
-    let socket_path = "connect_tool.sock";
+    return "connect_tool.sock";
     #[cfg(not(windows))]
-    let socket_path = "/tmp/connect_tool.sock";
-
-    // We need to ignore the uri in the connector
-    let channel = Endpoint::try_from("http://[::]:50051")
-        .map_err(|e| e.to_string())?
-        .connect_with_connector(service_fn(move |_: Uri| {
-            // Connect to UDS
-            connect_uds(socket_path)
-        }))
-        .await
-        .map_err(|e| format!("Failed to connect to UDS at {}: {}", socket_path, e))?;
-
-    Ok(ConnectToolServiceClient::new(channel))
+    return "/tmp/connect_tool.sock";
 }
 
-#[tauri::command]
-async fn create_lobby() -> Result<CreateLobbyResponse, String> {
-    let mut client = get_client().await?;
-    let response = client
-        .create_lobby(CreateLobbyRequest {})
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(response.into_inner())
+// Environment variable that overrides the socket path, for CI and development setups
+const SOCKET_PATH_ENV: &str = "CONNECT_TOOL_SOCKET";
+
+// Shown with socket connection errors so users can tell which path was attempted
+const SOCKET_PATH_PRECEDENCE: &str = "CONNECT_TOOL_SOCKET, then the saved socket path, then the platform default";
+
+/// Where the socket path in use came from
+#[derive(Clone, Copy, Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SocketPathSource {
+    Env,
+    Settings,
+    Default,
 }
 
-#[tauri::command]
-async fn join_lobby(lobby_id: String) -> Result<JoinLobbyResponse, String> {
-    let mut client = get_client().await?;
-    let response = client
-        .join_lobby(JoinLobbyRequest { lobby_id })
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(response.into_inner())
+impl std::fmt::Display for SocketPathSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SocketPathSource::Env => write!(f, "{}", SOCKET_PATH_ENV),
+            SocketPathSource::Settings => write!(f, "saved socket path"),
+            SocketPathSource::Default => write!(f, "platform default"),
+        }
+    }
 }
 
-#[tauri::command]
-async fn leave_lobby() -> Result<LeaveLobbyResponse, String> {
-    let mut client = get_client().await?;
-    let response = client
-        .leave_lobby(LeaveLobbyRequest {})
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(response.into_inner())
+/// Socket path set through CONNECT_TOOL_SOCKET, ignored when empty
+fn env_socket_path() -> Option<String> {
+    std::env::var(SOCKET_PATH_ENV)
+        .ok()
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty())
 }
 
-#[tauri::command]
-async fn get_lobby_info() -> Result<GetLobbyInfoResponse, String> {
-    let mut client = get_client().await?;
-    let response = client
-        .get_lobby_info(GetLobbyInfoRequest {})
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(response.into_inner())
+/// Current socket path and where it came from
+fn socket_path_with_source() -> (String, SocketPathSource) {
+    if let Some(path) = env_socket_path() {
+        return (path, SocketPathSource::Env);
+    }
+    read_settings(|s| match &s.socket_path {
+        Some(path) => (path.clone(), SocketPathSource::Settings),
+        None => (default_socket_path().to_string(), SocketPathSource::Default),
+    })
 }
 
-#[tauri::command]
-async fn get_friend_lobbies() -> Result<GetFriendLobbiesResponse, String> {
-    let mut client = get_client().await?;
-    let response = client
-        .get_friend_lobbies(GetFriendLobbiesRequest {})
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(response.into_inner())
+/// Current socket path
+fn socket_path() -> String {
+    socket_path_with_source().0
 }
 
-#[tauri::command]
-async fn invite_friend(friend_steam_id: String) -> Result<InviteFriendResponse, String> {
-    let mut client = get_client().await?;
-    let response = client
-        .invite_friend(InviteFriendRequest { friend_steam_id })
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(response.into_inner())
+/// Address of a ConnectToolCore reachable over TCP
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+struct TcpTarget {
+    host: String,
+    port: u16,
 }
 
-#[tauri::command]
-async fn get_vpn_status() -> Result<GetVpnStatusResponse, String> {
-    let mut client = get_client().await?;
-    let response = client
-        .get_vpn_status(GetVpnStatusRequest {})
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(response.into_inner())
+/// TLS settings for the TCP connection mode
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct TlsConfig {
+    ca_pem: String,
+    /// Client certificate, for cores requiring mutual TLS
+    client_cert_pem: Option<String>,
+    /// File holding the client certificate's private key. Only the path is persisted,
+    /// the key is read when connecting.
+    client_key_path: Option<PathBuf>,
+    /// Certificate and key PEM saved by older versions, dropped on the next save
+    #[serde(rename = "client_identity", default, skip_serializing)]
+    legacy_client_identity: Option<serde_json::Value>,
 }
 
-#[tauri::command]
-async fn get_vpn_routing_table() -> Result<GetVpnRoutingTableResponse, String> {
-    let mut client = get_client().await?;
-    let response = client
-        .get_vpn_routing_table(GetVpnRoutingTableRequest {})
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(response.into_inner())
+/// Client certificate and key for mutual TLS, reading the key from its file
+fn load_tls_identity(tls: &TlsConfig) -> Result<Option<Identity>, String> {
+    let (Some(cert), Some(key_path)) = (&tls.client_cert_pem, &tls.client_key_path) else {
+        return Ok(None);
+    };
+    let key = std::fs::read_to_string(key_path)
+        .map_err(|e| format!("Failed to read client key {}: {}", key_path.display(), e))?;
+    Ok(Some(Identity::from_pem(cert, key)))
 }
 
-// ============== Steam Management Commands ==============
+/// Check that a PEM string holds at least one certificate
+fn validate_certificate_pem(pem: &str, what: &str) -> Result<(), String> {
+    let certs = rustls_pemfile::certs(&mut pem.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to parse {}: {}", what, e))?;
+    if certs.is_empty() {
+        return Err(format!("No certificate found in {}", what));
+    }
+    Ok(())
+}
 
-#[tauri::command]
-async fn find_steam() -> Result<FindSteamPathResponse, String> {
-    match find_steam_path() {
-        Some(steam_path) => {
-            let steam_exe = get_steam_exe_path(&steam_path);
-            Ok(FindSteamPathResponse {
-                found: true,
-                steam_path: Some(steam_path.to_string_lossy().to_string()),
-                steam_exe_path: steam_exe.map(|p| p.to_string_lossy().to_string()),
-                message: "Steam found successfully".to_string(),
-            })
-        }
-        None => Ok(FindSteamPathResponse {
-            found: false,
-            steam_path: None,
-            steam_exe_path: None,
-            message: "Steam installation not found".to_string(),
-        }),
+/// Check that a PEM string holds a private key
+fn validate_private_key_pem(pem: &str) -> Result<(), String> {
+    match rustls_pemfile::private_key(&mut pem.as_bytes()) {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err("No private key found in client key".to_string()),
+        Err(e) => Err(format!("Failed to parse client key: {}", e)),
     }
 }
 
-#[tauri::command]
-async fn get_steam_running_status() -> Result<GetSteamStatusResponse, String> {
-    let pid = is_steam_running();
-    Ok(GetSteamStatusResponse {
-        is_running: pid.is_some(),
-        process_id: pid,
-    })
+/// How the app reaches ConnectToolCore
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ConnectionMode {
+    Uds { path: String },
+    Tcp { host: String, port: u16 },
 }
 
-#[tauri::command]
-async fn restart_steam_china() -> Result<RestartSteamChinaResponse, String> {
-    // Find Steam path
-    let steam_path = match find_steam_path() {
-        Some(path) => path,
-        None => {
-            return Ok(RestartSteamChinaResponse {
-                success: false,
-                message: "Steam installation not found".to_string(),
-            });
-        }
-    };
+/// Current connection mode, UDS unless a TCP target is configured
+fn connection_mode() -> ConnectionMode {
+    match read_settings(|s| s.tcp_target.clone()) {
+        Some(TcpTarget { host, port }) => ConnectionMode::Tcp { host, port },
+        None => ConnectionMode::Uds {
+            path: socket_path(),
+        },
+    }
+}
 
-    // Get Steam executable path
-    let steam_exe = match get_steam_exe_path(&steam_path) {
-        Some(exe) => exe,
-        None => {
-            return Ok(RestartSteamChinaResponse {
-                success: false,
-                message: "Steam executable not found".to_string(),
-            });
-        }
-    };
+// Error returned when ConnectToolCore does not answer within the configured timeouts
+const CORE_UNREACHABLE_TIMEOUT: &str = "core unreachable (timeout)";
 
-    // Check if Steam is running and kill it
-    if is_steam_running().is_some() {
-        if let Err(e) = kill_steam_process() {
-            return Ok(RestartSteamChinaResponse {
-                success: false,
-                message: format!("Failed to stop Steam: {}", e),
-            });
+/// Error returned by commands, tagged by kind so the frontend can react to each case
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "kind", content = "detail", rename_all = "snake_case")]
+pub enum CommandError {
+    /// ConnectToolCore is not reachable at the given socket path or host:port
+    NotConnected { address: String },
+    /// ConnectToolCore did not answer within the configured timeouts
+    Timeout,
+    /// ConnectToolCore answered with an error status
+    Rpc { code: i32, message: String },
+    /// The request was cancelled through cancel_request
+    Cancelled,
+    /// The managed core is running but not accepting connections yet
+    CoreStarting,
+    /// No managed core is running and the socket doesn't exist
+    CoreAbsent { path: String, source: SocketPathSource },
+    /// The directory the socket should live in doesn't exist, so no core can have bound it
+    SocketDirMissing { dir: String, path: String, source: SocketPathSource },
+    /// The socket exists but belongs to another user, e.g. a core started with sudo
+    SocketPermissionDenied { path: String, source: SocketPathSource, detail: String },
+    /// The socket exists but nothing accepts connections on it, usually left by a crashed core
+    SocketRefused { path: String, source: SocketPathSource, detail: String },
+    /// Changing or reading the firewall failed, classified from the raw error text
+    Firewall { code: FirewallErrorCode, raw: String },
+    /// The ConnectToolCore executable is missing, set_core_path_override can point elsewhere
+    CoreNotFound { searched_path: String },
+    /// A mutating command was called while read-only mode is enabled
+    ReadOnly,
+    /// Any other failure
+    Internal(String),
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandError::NotConnected { address } => {
+                write!(f, "ConnectToolCore is not connected (tried {})", address)
+            }
+            CommandError::Timeout => write!(f, "{}", CORE_UNREACHABLE_TIMEOUT),
+            CommandError::Rpc { code, message } => write!(f, "RPC error {}: {}", code, message),
+            CommandError::Cancelled => write!(f, "Request cancelled"),
+            CommandError::CoreStarting => write!(f, "ConnectToolCore is starting"),
+            CommandError::CoreAbsent { path, source } => write!(
+                f,
+                "ConnectToolCore is not running, no socket at {} (from {}; precedence: {})",
+                path, source, SOCKET_PATH_PRECEDENCE
+            ),
+            CommandError::SocketDirMissing { dir, path, source } => write!(
+                f,
+                "socket directory missing: {} (socket {} from {}; precedence: {})",
+                dir, path, source, SOCKET_PATH_PRECEDENCE
+            ),
+            CommandError::SocketPermissionDenied { path, source, detail } => write!(
+                f,
+                "Permission denied on socket {} (from {}; precedence: {}): {}",
+                path, source, SOCKET_PATH_PRECEDENCE, detail
+            ),
+            CommandError::SocketRefused { path, source, detail } => write!(
+                f,
+                "Connection refused on socket {} (from {}; precedence: {}): {}",
+                path, source, SOCKET_PATH_PRECEDENCE, detail
+            ),
+            CommandError::Firewall { raw, .. } => write!(f, "{}", raw),
+            CommandError::CoreNotFound { searched_path } => {
+                write!(f, "ConnectToolCore not found at: {}", searched_path)
+            }
+            CommandError::ReadOnly => write!(f, "Not allowed in read-only mode"),
+            CommandError::Internal(message) => write!(f, "{}", message),
         }
     }
+}
 
-    // Start Steam with -steamchina
-    match start_steam_china(&steam_exe) {
-        Ok(()) => Ok(RestartSteamChinaResponse {
-            success: true,
-            message: "Steam started with -steamchina parameter".to_string(),
-        }),
-        Err(e) => Ok(RestartSteamChinaResponse {
-            success: false,
-            message: e,
-        }),
+impl CommandError {
+    /// NotConnected for the address of the current connection mode
+    fn not_connected() -> Self {
+        let address = match connection_mode() {
+            ConnectionMode::Uds { path } => path,
+            ConnectionMode::Tcp { host, port } => format!("{}:{}", host, port),
+        };
+        CommandError::NotConnected { address }
     }
 }
 
-// ============== End Steam Management Commands ==============
-
-// ============== Firewall Management ==============
-
-/// Response structure for firewall status
-#[derive(serde::Serialize)]
-pub struct FirewallStatusResponse {
-    pub domain_enabled: bool,
-    pub private_enabled: bool,
-    pub public_enabled: bool,
-    pub message: String,
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        CommandError::Internal(message)
+    }
 }
 
-/// Response structure for firewall toggle
-#[derive(serde::Serialize)]
-pub struct FirewallToggleResponse {
-    pub success: bool,
-    pub message: String,
+impl From<&str> for CommandError {
+    fn from(message: &str) -> Self {
+        CommandError::Internal(message.to_string())
+    }
 }
 
-/// Response structure for core status
-#[derive(serde::Serialize)]
-pub struct CoreStatusResponse {
-    pub is_running: bool,
-    pub pid: Option<u32>,
-    pub message: String,
+/// Timeouts applied to the gRPC channel
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub struct ConnectionTimeouts {
+    pub connect_timeout_ms: u64,
+    pub request_timeout_ms: u64,
 }
 
-/// Response structure for core control
-#[derive(serde::Serialize)]
-pub struct CoreControlResponse {
-    pub success: bool,
-    pub is_running: bool,
-    pub pid: Option<u32>,
-    pub message: String,
-}
+// Used until the user saves their own timeouts
+const DEFAULT_CONNECTION_TIMEOUTS: ConnectionTimeouts = ConnectionTimeouts {
+    connect_timeout_ms: 5000,
+    request_timeout_ms: 10000,
+};
 
-/// Get Windows Firewall status for all profiles
-#[cfg(windows)]
-fn get_firewall_status_windows() -> Result<FirewallStatusResponse, String> {
-    use std::os::windows::process::CommandExt;
-    const CREATE_NO_WINDOW: u32 = 0x08000000;
-    
-    let output = Command::new("powershell")
-        .args([
-            "-Command",
-            "Get-NetFirewallProfile | Select-Object -Property Name, Enabled | ConvertTo-Json"
-        ])
-        .creation_flags(CREATE_NO_WINDOW)
-        .output()
-        .map_err(|e| format!("Failed to execute PowerShell: {}", e))?;
+/// Connection timeouts in effect
+fn connection_timeouts() -> ConnectionTimeouts {
+    read_settings(|s| s.connection_timeouts).unwrap_or(DEFAULT_CONNECTION_TIMEOUTS)
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("PowerShell command failed: {}", stderr));
+/// First io::Error in an error's source chain
+fn find_io_error<'a>(err: &'a (dyn std::error::Error + 'static)) -> Option<&'a std::io::Error> {
+    let mut source = Some(err);
+    while let Some(err) = source {
+        if let Some(io_err) = err.downcast_ref::<std::io::Error>() {
+            return Some(io_err);
+        }
+        source = err.source();
     }
+    None
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    
-    // Parse JSON output
-    let profiles: serde_json::Value = serde_json::from_str(&stdout)
-        .map_err(|e| format!("Failed to parse firewall status: {}", e))?;
-    
-    let mut domain_enabled = false;
-    let mut private_enabled = false;
-    let mut public_enabled = false;
-    
-    if let Some(arr) = profiles.as_array() {
-        for profile in arr {
-            let name = profile.get("Name").and_then(|v| v.as_str()).unwrap_or("");
-            // Enabled can be a bool (true/false) or a number (1/0)
-            let enabled = profile.get("Enabled").map(|v| {
-                v.as_bool().unwrap_or_else(|| {
-                    v.as_i64().map(|n| n != 0).unwrap_or(false)
-                })
-            }).unwrap_or(false);
-            
-            match name {
-                "Domain" => domain_enabled = enabled,
-                "Private" => private_enabled = enabled,
-                "Public" => public_enabled = enabled,
-                _ => {}
+/// Check whether a transport error was caused by the connect timeout
+fn is_timeout_error(err: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(err);
+    while let Some(err) = source {
+        if let Some(io_err) = err.downcast_ref::<std::io::Error>() {
+            if io_err.kind() == std::io::ErrorKind::TimedOut {
+                return true;
             }
         }
+        source = err.source();
     }
-    
-    Ok(FirewallStatusResponse {
-        domain_enabled,
-        private_enabled,
-        public_enabled,
-        message: "Firewall status retrieved successfully".to_string(),
-    })
+    false
 }
 
-/// Set Windows Firewall status
-#[cfg(windows)]
-fn set_firewall_status_windows(enabled: bool) -> Result<FirewallToggleResponse, String> {
-    use std::os::windows::process::CommandExt;
-    const CREATE_NO_WINDOW: u32 = 0x08000000;
+/// Check whether an error is a transport failure that may succeed on retry,
+/// e.g. the core restarting and briefly refusing or resetting connections
+fn is_transient_error(err: &(dyn std::error::Error + 'static)) -> bool {
+    use std::io::ErrorKind;
+
+    let mut source = Some(err);
+    while let Some(err) = source {
+        if let Some(io_err) = err.downcast_ref::<std::io::Error>() {
+            return matches!(
+                io_err.kind(),
+                ErrorKind::ConnectionRefused
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::BrokenPipe
+            );
+        }
+        source = err.source();
+    }
+    false
+}
+
+// A connected client with the CLIENT_GENERATION it was connected under
+type CachedClient = (u64, ConnectToolServiceClient<Channel>);
+
+// Shared gRPC client, connected lazily on first use and rebuilt after transport failures
+static CLIENT: Lazy<tokio::sync::Mutex<Option<CachedClient>>> =
+    Lazy::new(|| tokio::sync::Mutex::new(None));
+
+// Bumped whenever the connection target changes, a client or connect attempt from an
+// older generation is stale and must not be used
+static CLIENT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+// Wakes in-flight connect attempts when the generation changes
+static CLIENT_GENERATION_CHANGED: Lazy<tokio::sync::Notify> = Lazy::new(tokio::sync::Notify::new);
+
+/// Abandon the cached client and any in-flight connect, the next call connects to the current target
+fn reset_client() {
+    // Lobby state seen on the old target says nothing about the new one
+    *LOBBY_STATE.lock().unwrap() = None;
+    CLIENT_GENERATION.fetch_add(1, Ordering::SeqCst);
+    CLIENT_GENERATION_CHANGED.notify_waiters();
+}
+
+/// Open a new channel to ConnectToolCore using the configured connection mode
+#[tracing::instrument]
+async fn connect_client() -> Result<ConnectToolServiceClient<Channel>, tonic::transport::Error> {
+    let timeouts = connection_timeouts();
+
+    let channel = match connection_mode() {
+        ConnectionMode::Tcp { host, port } => {
+            let tls = read_settings(|s| s.tls_config.clone());
+            let scheme = if tls.is_some() { "https" } else { "http" };
+            // IPv6 literals need brackets inside a URI
+            let uri_host = if host.contains(':') && !host.starts_with('[') {
+                format!("[{}]", host)
+            } else {
+                host.clone()
+            };
+
+            let mut endpoint = Endpoint::from_shared(format!("{}://{}:{}", scheme, uri_host, port))?
+                .connect_timeout(Duration::from_millis(timeouts.connect_timeout_ms))
+                .timeout(Duration::from_millis(timeouts.request_timeout_ms));
+            if let Some(tls) = tls {
+                let mut tls_config = ClientTlsConfig::new()
+                    .domain_name(host)
+                    .ca_certificate(Certificate::from_pem(&tls.ca_pem));
+                // Without its key the core rejects the handshake, which surfaces as the connect error
+                match load_tls_identity(&tls) {
+                    Ok(Some(identity)) => tls_config = tls_config.identity(identity),
+                    Ok(None) => {}
+                    Err(e) => tracing::error!(error = %e, "connecting without a client certificate"),
+                }
+                endpoint = endpoint.tls_config(tls_config)?;
+            }
+            endpoint.connect().await?
+        }
+        ConnectionMode::Uds { path: socket_path } => {
+            // We need to ignore the uri in the connector
+            Endpoint::try_from("http://[::]:50051")?
+                .connect_timeout(Duration::from_millis(timeouts.connect_timeout_ms))
+                .timeout(Duration::from_millis(timeouts.request_timeout_ms))
+                .connect_with_connector(service_fn(move |_: Uri| {
+                    // Connect to UDS
+                    let path = socket_path.clone();
+                    async move { connect_uds(&path).await }
+                }))
+                .await?
+        }
+    };
+
+    Ok(ConnectToolServiceClient::new(channel))
+}
+
+/// Classify a failure to connect to ConnectToolCore, using the managed process state
+/// to tell a core that is still starting from one that isn't there at all
+fn connect_error(err: &tonic::transport::Error) -> CommandError {
+    use std::io::ErrorKind;
+
+    if is_timeout_error(err) {
+        return CommandError::Timeout;
+    }
+    let io_error = find_io_error(err);
+    let uds = match connection_mode() {
+        ConnectionMode::Uds { .. } => Some(socket_path_with_source()),
+        ConnectionMode::Tcp { .. } => None,
+    };
+    if let Some((ref path, source)) = uds {
+        if let Some(dir) = missing_socket_dir(path) {
+            return CommandError::SocketDirMissing {
+                dir: dir.to_string_lossy().to_string(),
+                path: path.clone(),
+                source,
+            };
+        }
+        // Waiting for a starting core won't fix this
+        if let Some(io_err) = io_error.filter(|e| e.kind() == ErrorKind::PermissionDenied) {
+            return CommandError::SocketPermissionDenied {
+                path: path.clone(),
+                source,
+                detail: io_err.to_string(),
+            };
+        }
+    }
+    if check_core_process_running().0 {
+        return CommandError::CoreStarting;
+    }
+    match uds {
+        Some((path, source)) if !Path::new(&path).exists() => CommandError::CoreAbsent { path, source },
+        Some((path, source)) => match io_error.filter(|e| e.kind() == ErrorKind::ConnectionRefused) {
+            Some(io_err) => CommandError::SocketRefused {
+                path,
+                source,
+                detail: io_err.to_string(),
+            },
+            None => CommandError::NotConnected { address: path },
+        },
+        None => CommandError::not_connected(),
+    }
+}
+
+/// Get the shared client, connecting if needed
+async fn try_get_client() -> Result<ConnectToolServiceClient<Channel>, tonic::transport::Error> {
+    let mut guard = CLIENT.lock().await;
+
+    loop {
+        // Registered before reading the generation so a reset in between still wakes us
+        let changed = CLIENT_GENERATION_CHANGED.notified();
+        let generation = CLIENT_GENERATION.load(Ordering::SeqCst);
+
+        // Cloning is cheap, all clones share the same underlying channel
+        match *guard {
+            Some((cached, ref client)) if cached == generation => return Ok(client.clone()),
+            _ => *guard = None,
+        }
+
+        let result = tokio::select! {
+            result = connect_client() => result,
+            _ = changed => {
+                tracing::debug!("connection target changed, abandoning connect attempt");
+                continue;
+            }
+        };
+        if CLIENT_GENERATION.load(Ordering::SeqCst) != generation {
+            // Connected to, or failed on, a target that is no longer configured
+            continue;
+        }
+
+        let client = result.inspect_err(|e| {
+            tracing::warn!(error = %e, mode = ?connection_mode(), "failed to connect to ConnectToolCore");
+        })?;
+        tracing::debug!("connected to ConnectToolCore");
+        // The new channel may lead to a different core build
+        CORE_FEATURE_SUPPORT.lock().unwrap().clear();
+        *guard = Some((generation, client.clone()));
+        return Ok(client);
+    }
+}
+
+// Helper to get client
+async fn get_client() -> Result<ConnectToolServiceClient<Channel>, CommandError> {
+    try_get_client().await.map_err(|e| connect_error(&e))
+}
+
+/// Drop the cached client so the next call reconnects
+fn invalidate_client() {
+    // If the lock is busy, another call is already establishing a fresh connection
+    if let Ok(mut guard) = CLIENT.try_lock() {
+        *guard = None;
+    }
+}
+
+/// Classify an RPC failure, discarding the cached client if the transport broke
+fn rpc_error(status: tonic::Status) -> CommandError {
+    // Request timeouts surface as CANCELLED with tonic's TimeoutExpired message
+    if status.code() == tonic::Code::Cancelled
+        && status.message() == tonic::TimeoutExpired(()).to_string()
+    {
+        return CommandError::Timeout;
+    }
+    if status.code() == tonic::Code::Unavailable {
+        tracing::warn!(status = %status, "transport to ConnectToolCore broke, reconnecting on next call");
+        invalidate_client();
+        if is_timeout_error(&status) {
+            return CommandError::Timeout;
+        }
+        return CommandError::not_connected();
+    }
+    CommandError::Rpc {
+        code: status.code() as i32,
+        message: status.message().to_string(),
+    }
+}
+
+/// Run an RPC against the shared client, retrying transport-level failures (such as the core
+/// being mid-restart) with backoff. Errors returned by the core itself are not retried.
+/// Only for RPCs that are safe to repeat, see with_connect_retry for the others.
+async fn with_retry<T, F, Fut>(call: F) -> Result<T, CommandError>
+where
+    F: FnMut(ConnectToolServiceClient<Channel>) -> Fut,
+    Fut: std::future::Future<Output = Result<tonic::Response<T>, tonic::Status>>,
+{
+    retry_rpc(true, call).await
+}
+
+/// Like with_retry, but for RPCs that change state in the core. Unavailable can come back after
+/// the core already handled the request, so only failures to connect are retried.
+async fn with_connect_retry<T, F, Fut>(call: F) -> Result<T, CommandError>
+where
+    F: FnMut(ConnectToolServiceClient<Channel>) -> Fut,
+    Fut: std::future::Future<Output = Result<tonic::Response<T>, tonic::Status>>,
+{
+    retry_rpc(false, call).await
+}
+
+/// Shared loop of with_retry and with_connect_retry
+async fn retry_rpc<T, F, Fut>(idempotent: bool, mut call: F) -> Result<T, CommandError>
+where
+    F: FnMut(ConnectToolServiceClient<Channel>) -> Fut,
+    Fut: std::future::Future<Output = Result<tonic::Response<T>, tonic::Status>>,
+{
+    const BACKOFF_MS: [u64; 3] = [100, 300, 900];
+
+    let mut attempt = 0;
+    loop {
+        let error = match try_get_client().await {
+            Ok(client) => match call(client).await {
+                Ok(response) => return Ok(response.into_inner()),
+                Err(status) => {
+                    let transient = idempotent
+                        && status.code() == tonic::Code::Unavailable
+                        && !is_timeout_error(&status);
+                    let error = rpc_error(status);
+                    if !transient {
+                        return Err(error);
+                    }
+                    error
+                }
+            },
+            Err(e) => {
+                let error = connect_error(&e);
+                // A starting core should bind its socket shortly
+                if !is_transient_error(&e) && !matches!(error, CommandError::CoreStarting) {
+                    return Err(error);
+                }
+                error
+            }
+        };
+
+        if attempt >= BACKOFF_MS.len() {
+            return Err(error);
+        }
+        tokio::time::sleep(Duration::from_millis(BACKOFF_MS[attempt])).await;
+        attempt += 1;
+    }
+}
+
+// Cancellation tokens of in-flight requests, keyed by the id supplied by the frontend
+static PENDING_REQUESTS: Lazy<Mutex<HashMap<String, CancellationToken>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Run a command body that can be aborted by calling cancel_request with the same id.
+/// Without an id the body simply runs to completion, an id already in flight is rejected.
+///
+/// Cancelling only drops the future on this side: the RPC is abandoned, but the core
+/// keeps working on whatever it had already received.
+async fn cancellable<T>(
+    request_id: Option<String>,
+    body: impl std::future::Future<Output = Result<T, CommandError>>,
+) -> Result<T, CommandError> {
+    let Some(request_id) = request_id else {
+        return body.await;
+    };
+
+    let token = CancellationToken::new();
+    match PENDING_REQUESTS.lock().unwrap().entry(request_id.clone()) {
+        std::collections::hash_map::Entry::Occupied(_) => {
+            return Err(format!("Request id {} is already in use", request_id).into());
+        }
+        std::collections::hash_map::Entry::Vacant(entry) => {
+            entry.insert(token.clone());
+        }
+    }
+
+    // Dropping the body on cancellation also drops the in-flight RPC
+    let result = tokio::select! {
+        result = body => result,
+        _ = token.cancelled() => Err(CommandError::Cancelled),
+    };
+
+    // A cancelled token was already removed, and its id may now belong to a newer request
+    if !token.is_cancelled() {
+        PENDING_REQUESTS.lock().unwrap().remove(&request_id);
+    }
+    result
+}
+
+/// Cancel an in-flight request, returning whether one was found
+#[tauri::command]
+#[tracing::instrument]
+async fn cancel_request(id: String) -> Result<bool, CommandError> {
+    match PENDING_REQUESTS.lock().unwrap().remove(&id) {
+        Some(token) => {
+            token.cancel();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+// When set, RPC-backed commands answer from the mock module without touching the socket
+static MOCK_MODE: AtomicBool = AtomicBool::new(false);
+
+fn mock_mode() -> bool {
+    MOCK_MODE.load(Ordering::SeqCst)
+}
+
+// When set, commands that change lobby, VPN, firewall, Steam or core state are refused
+static READONLY_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Refuse a mutating command in read-only mode, before anything is contacted
+fn ensure_writable() -> Result<(), CommandError> {
+    if READONLY_MODE.load(Ordering::SeqCst) {
+        return Err(CommandError::ReadOnly);
+    }
+    Ok(())
+}
+
+/// Enable or disable read-only mode for monitoring setups, returning the new state
+#[tauri::command]
+#[tracing::instrument]
+async fn set_readonly_mode(enabled: bool) -> Result<bool, CommandError> {
+    READONLY_MODE.store(enabled, Ordering::SeqCst);
+    tracing::info!(enabled, "read-only mode changed");
+    Ok(enabled)
+}
+
+/// Enable or disable mock mode for UI development, returning the new state
+#[tauri::command]
+#[tracing::instrument]
+async fn set_mock_mode(enabled: bool) -> Result<bool, CommandError> {
+    MOCK_MODE.store(enabled, Ordering::SeqCst);
+    tracing::info!(enabled, "mock mode changed");
+    Ok(enabled)
+}
+
+/// Response structure for set_socket_path command
+#[derive(serde::Serialize)]
+pub struct SocketPathResponse {
+    pub socket_path: String,
+    pub message: String,
+}
+
+/// Check a socket path entered by the user, returning it trimmed
+fn validate_socket_path(path: &str) -> Result<String, String> {
+    let path = path.trim().to_string();
+    if path.is_empty() {
+        return Err("Socket path cannot be empty".to_string());
+    }
+
+    // A bare file name is resolved against the working directory
+    if let Some(parent) = Path::new(&path).parent() {
+        if !parent.as_os_str().is_empty() && !parent.is_dir() {
+            return Err(format!("Socket directory does not exist: {}", parent.display()));
+        }
+    }
+    Ok(path)
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn set_socket_path(path: String) -> Result<SocketPathResponse, CommandError> {
+    ensure_writable()?;
+    let path = validate_socket_path(&path)?;
+
+    modify_settings(|s| s.socket_path = Some(path.clone()))?;
+
+    // Reconnect on the next call using the new path
+    reset_client();
+
+    let message = match env_socket_path() {
+        Some(env_path) => format!(
+            "Socket path saved as {}, but {}={} takes precedence",
+            path, SOCKET_PATH_ENV, env_path
+        ),
+        None => format!("Socket path set to {}", path),
+    };
+    Ok(SocketPathResponse {
+        message,
+        socket_path: path,
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_connection_mode() -> Result<ConnectionMode, CommandError> {
+    Ok(connection_mode())
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn set_connection_mode(mode: ConnectionMode) -> Result<ConnectionMode, CommandError> {
+    ensure_writable()?;
+    match mode {
+        ConnectionMode::Uds { path } => {
+            set_socket_path(path).await?;
+            modify_settings(|s| s.tcp_target = None)?;
+        }
+        ConnectionMode::Tcp { host, port } => {
+            let host = host.trim().to_string();
+            if host.is_empty() {
+                return Err("Host cannot be empty".into());
+            }
+            if port == 0 {
+                return Err("Port must be greater than zero".into());
+            }
+            let target = TcpTarget { host, port };
+            modify_settings(|s| s.tcp_target = Some(target))?;
+        }
+    }
+
+    // Reconnect on the next call using the new transport
+    reset_client();
+
+    Ok(connection_mode())
+}
+
+/// Response structure for set_tls_config command
+#[derive(serde::Serialize)]
+pub struct TlsConfigResponse {
+    pub enabled: bool,
+    pub client_identity: bool,
+    /// Where the client key is read from, the key itself is never returned
+    pub client_key_path: Option<String>,
+    pub message: String,
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(ca_pem, client_cert))]
+async fn set_tls_config(
+    ca_pem: String,
+    client_cert: Option<String>,
+    client_key_path: Option<String>,
+) -> Result<TlsConfigResponse, CommandError> {
+    validate_certificate_pem(&ca_pem, "CA certificate")?;
+    let (client_cert_pem, client_key_path) = match (client_cert, client_key_path) {
+        (Some(cert), Some(key_path)) => {
+            validate_certificate_pem(&cert, "client certificate")?;
+            let key_path = PathBuf::from(key_path.trim());
+            let key = std::fs::read_to_string(&key_path)
+                .map_err(|e| format!("Failed to read client key {}: {}", key_path.display(), e))?;
+            validate_private_key_pem(&key)?;
+            (Some(cert), Some(key_path))
+        }
+        (None, None) => (None, None),
+        _ => return Err("Client certificate and key path must be provided together".into()),
+    };
+
+    let config = TlsConfig {
+        ca_pem,
+        client_cert_pem,
+        client_key_path,
+        legacy_client_identity: None,
+    };
+    let client_key_path = config
+        .client_key_path
+        .as_ref()
+        .map(|path| path.to_string_lossy().to_string());
+    modify_settings(|s| s.tls_config = Some(config))?;
+
+    // Reconnect on the next call with TLS applied
+    reset_client();
+
+    Ok(TlsConfigResponse {
+        enabled: true,
+        client_identity: client_key_path.is_some(),
+        client_key_path,
+        message: "TLS will be used for TCP connections".to_string(),
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn clear_tls_config() -> Result<TlsConfigResponse, CommandError> {
+    modify_settings(|s| s.tls_config = None)?;
+    reset_client();
+
+    Ok(TlsConfigResponse {
+        enabled: false,
+        client_identity: false,
+        client_key_path: None,
+        message: "TCP connections will be plaintext".to_string(),
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn set_connection_timeouts(
+    connect_timeout_ms: u64,
+    request_timeout_ms: u64,
+) -> Result<ConnectionTimeouts, CommandError> {
+    if connect_timeout_ms == 0 || request_timeout_ms == 0 {
+        return Err("Timeouts must be greater than zero".into());
+    }
+
+    let timeouts = ConnectionTimeouts {
+        connect_timeout_ms,
+        request_timeout_ms,
+    };
+    modify_settings(|s| s.connection_timeouts = Some(timeouts))?;
+
+    // Rebuild the channel so the new timeouts take effect
+    reset_client();
+
+    Ok(timeouts)
+}
+
+/// Remember the lobby we are in so rejoin_last_lobby can return to it after a restart
+fn remember_lobby(lobby_id: &str) {
+    if let Err(e) = modify_settings(|s| s.last_lobby = Some(lobby_id.to_string())) {
+        tracing::warn!(error = %e, "failed to persist last lobby");
+    }
+}
+
+/// Forget the remembered lobby, there is nothing to rejoin
+fn forget_lobby() {
+    if let Err(e) = modify_settings(|s| s.last_lobby = None) {
+        tracing::warn!(error = %e, "failed to clear last lobby");
+    }
+}
+
+// Whether the core was last seen in a lobby, and when. The core can join lobbies on its
+// own (e.g. a Steam invite), so this is only trusted while fresh.
+static LOBBY_STATE: Lazy<Mutex<Option<(bool, std::time::Instant)>>> = Lazy::new(|| Mutex::new(None));
+
+// How long an observed lobby state is trusted by leave_lobby
+const LOBBY_STATE_MAX_AGE: Duration = Duration::from_secs(5);
+
+fn record_lobby_state(in_lobby: bool) {
+    *LOBBY_STATE.lock().unwrap() = Some((in_lobby, std::time::Instant::now()));
+}
+
+/// Whether we recently observed that the core is not in a lobby
+fn known_not_in_lobby() -> bool {
+    matches!(
+        *LOBBY_STATE.lock().unwrap(),
+        Some((false, seen_at)) if seen_at.elapsed() <= LOBBY_STATE_MAX_AGE
+    )
+}
+
+/// Whether an RPC error means there was no lobby to leave. Only the core's not-in-lobby
+/// message counts, other failed preconditions are real errors.
+fn is_not_in_lobby_error(err: &CommandError) -> bool {
+    match err {
+        CommandError::Rpc { message, .. } => {
+            let message = message.to_lowercase();
+            message.contains("not in lobby") || message.contains("not in a lobby")
+        }
+        _ => false,
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn create_lobby(request_id: Option<String>) -> Result<CreateLobbyResponse, CommandError> {
+    ensure_writable()?;
+    if mock_mode() {
+        return Ok(mock::create_lobby());
+    }
+    let response = record_core_feature(
+        "lobby",
+        cancellable(
+            request_id,
+            with_connect_retry(|mut client| async move { client.create_lobby(CreateLobbyRequest {}).await }),
+        )
+        .await,
+    )?;
+    if response.success && !response.lobby_id.is_empty() {
+        remember_lobby(&response.lobby_id);
+        record_lobby_state(true);
+    }
+    Ok(response)
+}
+
+/// Normalize a user-entered lobby code, which is a Steam lobby ID (a 64-bit number)
+fn validate_lobby_id(input: &str) -> Result<String, String> {
+    let lobby_id = input.trim();
+    if lobby_id.is_empty()
+        || !lobby_id.bytes().all(|b| b.is_ascii_digit())
+        || lobby_id.parse::<u64>().map_or(true, |id| id == 0)
+    {
+        return Err("invalid lobby code format".to_string());
+    }
+    Ok(lobby_id.to_string())
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn join_lobby(
+    lobby_id: String,
+    request_id: Option<String>,
+) -> Result<JoinLobbyResponse, CommandError> {
+    ensure_writable()?;
+    let lobby_id = validate_lobby_id(&lobby_id)?;
+    if mock_mode() {
+        return Ok(mock::join_lobby(&lobby_id));
+    }
+    let response = record_core_feature(
+        "lobby",
+        cancellable(
+            request_id,
+            with_connect_retry(|mut client| {
+                let lobby_id = lobby_id.clone();
+                async move { client.join_lobby(JoinLobbyRequest { lobby_id }).await }
+            }),
+        )
+        .await,
+    )?;
+    if response.success {
+        remember_lobby(&lobby_id);
+        record_lobby_state(true);
+    }
+    Ok(response)
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn leave_lobby() -> Result<LeaveLobbyResponse, CommandError> {
+    ensure_writable()?;
+    if mock_mode() {
+        return Ok(mock::leave_lobby());
+    }
+    // A repeated leave, e.g. a double click, has nothing to do
+    if known_not_in_lobby() {
+        return Ok(LeaveLobbyResponse { success: true });
+    }
+    let result = with_connect_retry(|mut client| async move { client.leave_lobby(LeaveLobbyRequest {}).await }).await;
+    let response = match record_core_feature("lobby", result) {
+            Ok(response) => response,
+            Err(e) if is_not_in_lobby_error(&e) => LeaveLobbyResponse { success: true },
+            Err(e) => return Err(e),
+        };
+    // Leaving on purpose means there is nothing to rejoin
+    forget_lobby();
+    if response.success {
+        record_lobby_state(false);
+    }
+    Ok(response)
+}
+
+/// Outcome of rejoin_last_lobby
+#[derive(serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejoinLobbyResult {
+    Rejoined,
+    NoSavedLobby,
+    /// The saved lobby no longer exists or can't be joined, it is forgotten
+    LobbyGone,
+}
+
+/// Response structure for rejoin_last_lobby command
+#[derive(serde::Serialize)]
+pub struct RejoinLobbyResponse {
+    pub result: RejoinLobbyResult,
+    pub lobby_id: Option<String>,
+    pub message: String,
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn rejoin_last_lobby() -> Result<RejoinLobbyResponse, CommandError> {
+    let Some(lobby_id) = read_settings(|s| s.last_lobby.clone()) else {
+        return Ok(RejoinLobbyResponse {
+            result: RejoinLobbyResult::NoSavedLobby,
+            lobby_id: None,
+            message: "No previous lobby to rejoin".to_string(),
+        });
+    };
+
+    let response = join_lobby(lobby_id.clone(), None).await?;
+    if response.success {
+        return Ok(RejoinLobbyResponse {
+            result: RejoinLobbyResult::Rejoined,
+            lobby_id: Some(lobby_id),
+            message: response.message,
+        });
+    }
+
+    forget_lobby();
+    Ok(RejoinLobbyResponse {
+        result: RejoinLobbyResult::LobbyGone,
+        lobby_id: Some(lobby_id),
+        message: response.message,
+    })
+}
+
+/// Lobby state including the member roster
+#[tauri::command]
+#[tracing::instrument]
+async fn get_lobby_info() -> Result<GetLobbyInfoResponse, CommandError> {
+    if mock_mode() {
+        return Ok(mock::lobby_info());
+    }
+    let info = record_core_feature(
+        "lobby",
+        with_retry(|mut client| async move { client.get_lobby_info(GetLobbyInfoRequest {}).await }).await,
+    )?;
+    record_lobby_state(info.is_in_lobby);
+    Ok(normalize_lobby_info(info))
+}
+
+/// Fill in what a mismatched core may leave empty, so the lobby view still renders.
+/// Members without a Steam ID can't be told apart and are dropped.
+fn normalize_lobby_info(mut info: GetLobbyInfoResponse) -> GetLobbyInfoResponse {
+    info.lobby_id = info.lobby_id.trim().to_string();
+    info.members.retain(|member| !member.steam_id.trim().is_empty());
+    for member in &mut info.members {
+        if member.name.trim().is_empty() {
+            member.name = member.steam_id.clone();
+        }
+    }
+    info
+}
+
+/// A lobby member as shown in the roster
+#[derive(serde::Serialize)]
+pub struct LobbyMemberInfo {
+    pub steam_id: String,
+    pub display_name: String,
+    pub ping: i32,
+    pub relay_info: String,
+    /// The core's LobbyMember has no host or connection flag, so both stay None (unknown)
+    /// until its proto defines them
+    pub is_host: Option<bool>,
+    pub connected: Option<bool>,
+}
+
+/// Response structure for get_lobby_members command
+#[derive(serde::Serialize)]
+pub struct LobbyMembersResponse {
+    pub in_lobby: bool,
+    pub members: Vec<LobbyMemberInfo>,
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_lobby_members() -> Result<LobbyMembersResponse, CommandError> {
+    let info = get_lobby_info().await?;
+
+    // Not being in a lobby is a normal state, not an error
+    if !info.is_in_lobby {
+        return Ok(LobbyMembersResponse {
+            in_lobby: false,
+            members: Vec::new(),
+        });
+    }
+
+    Ok(LobbyMembersResponse {
+        in_lobby: true,
+        members: info
+            .members
+            .into_iter()
+            .map(|m| LobbyMemberInfo {
+                steam_id: m.steam_id,
+                display_name: m.name,
+                ping: m.ping,
+                relay_info: m.relay_info,
+                is_host: None,
+                connected: None,
+            })
+            .collect(),
+    })
+}
+
+/// Response structure for get_lobby_capacity command
+#[derive(serde::Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum LobbyCapacity {
+    NotInLobby,
+    /// The core's GetLobbyInfo carries no member limit or host flag, so those stay None
+    /// (unknown) until its proto defines them
+    InLobby {
+        current_members: u32,
+        max_members: Option<u32>,
+        is_full: Option<bool>,
+        is_host: Option<bool>,
+    },
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_lobby_capacity() -> Result<LobbyCapacity, CommandError> {
+    let info = get_lobby_info().await?;
+    if !info.is_in_lobby {
+        return Ok(LobbyCapacity::NotInLobby);
+    }
+
+    Ok(LobbyCapacity::InLobby {
+        current_members: info.members.len() as u32,
+        max_members: None,
+        is_full: None,
+        is_host: None,
+    })
+}
+
+/// Response structure for get_lobby_share_text command
+#[derive(serde::Serialize)]
+pub struct LobbyShareText {
+    pub lobby_id: String,
+    pub text: String,
+}
+
+/// Format the text users paste to friends so they can join the lobby
+fn format_lobby_share_text(lobby_id: &str) -> String {
+    format!("Join my ConnectTool lobby with code: {}", lobby_id)
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_lobby_share_text() -> Result<LobbyShareText, CommandError> {
+    let info = get_lobby_info().await?;
+    if !info.is_in_lobby {
+        return Err("Not currently in a lobby".into());
+    }
+
+    Ok(LobbyShareText {
+        text: format_lobby_share_text(&info.lobby_id),
+        lobby_id: info.lobby_id,
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(app))]
+async fn copy_lobby_share_text(app: tauri::AppHandle) -> Result<LobbyShareText, CommandError> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    let share = get_lobby_share_text().await?;
+    app.clipboard()
+        .write_text(share.text.clone())
+        .map_err(|e| format!("Failed to copy to clipboard: {}", e))?;
+    Ok(share)
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_friend_lobbies(
+    request_id: Option<String>,
+) -> Result<GetFriendLobbiesResponse, CommandError> {
+    if mock_mode() {
+        return Ok(mock::friend_lobbies());
+    }
+    record_core_feature(
+        "friend_lobbies",
+        cancellable(
+            request_id,
+            with_retry(|mut client| async move {
+                client.get_friend_lobbies(GetFriendLobbiesRequest {}).await
+            }),
+        )
+        .await,
+    )
+}
+
+/// A friend's lobby as shown in the friends panel, problems are reported per entry
+#[derive(serde::Serialize)]
+pub struct FriendLobbyEntry {
+    pub friend_steam_id: String,
+    pub display_name: String,
+    pub lobby_id: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Normalize one friend lobby from the core, keeping malformed entries with an error
+fn normalize_friend_lobby(lobby: FriendLobby) -> FriendLobbyEntry {
+    let lobby_id = lobby.lobby_id.trim().to_string();
+    let (lobby_id, error) = if lobby_id.is_empty() {
+        (None, Some("No lobby ID reported for this friend".to_string()))
+    } else if lobby_id.parse::<u64>().is_err() {
+        (None, Some(format!("Invalid lobby ID: {}", lobby_id)))
+    } else {
+        (Some(lobby_id), None)
+    };
+
+    FriendLobbyEntry {
+        display_name: if lobby.name.trim().is_empty() {
+            lobby.steam_id.clone()
+        } else {
+            lobby.name
+        },
+        friend_steam_id: lobby.steam_id,
+        lobby_id,
+        error,
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn list_friend_lobbies(request_id: Option<String>) -> Result<Vec<FriendLobbyEntry>, CommandError> {
+    let response = get_friend_lobbies(request_id).await?;
+    Ok(response.lobbies.into_iter().map(normalize_friend_lobby).collect())
+}
+
+/// Response structure for normalize_steam_id command
+#[derive(serde::Serialize)]
+pub struct NormalizeSteamIdResponse {
+    pub steam_id64: String,
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn normalize_steam_id(input: String) -> Result<NormalizeSteamIdResponse, CommandError> {
+    Ok(NormalizeSteamIdResponse {
+        steam_id64: steam_id::normalize(&input)?,
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn invite_friend(friend_steam_id: String) -> Result<InviteFriendResponse, CommandError> {
+    ensure_writable()?;
+    // Accept SteamID3, SteamID2 and profile URLs, the core only understands SteamID64
+    let friend_steam_id = steam_id::normalize(&friend_steam_id)?;
+    if mock_mode() {
+        return Ok(mock::invite_friend());
+    }
+    let result = with_connect_retry(|mut client| {
+        let friend_steam_id = friend_steam_id.clone();
+        async move { client.invite_friend(InviteFriendRequest { friend_steam_id }).await }
+    })
+    .await;
+    record_core_feature("invite_friend", result)
+}
+
+/// Outcome of one invite sent by invite_friends
+#[derive(serde::Serialize)]
+pub struct InviteResult {
+    pub steam_id: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn invite_friends(friend_steam_ids: Vec<String>) -> Result<Vec<InviteResult>, CommandError> {
+    // Normalize first so one friend given in several notations is invited once,
+    // dropping empty IDs and duplicates and keeping the caller's order
+    let mut seen = BTreeSet::new();
+    let invites: Vec<Result<String, InviteResult>> = friend_steam_ids
+        .iter()
+        .map(|id| id.trim())
+        .filter(|id| !id.is_empty())
+        .map(|id| {
+            steam_id::normalize(id).map_err(|e| InviteResult {
+                steam_id: id.to_string(),
+                success: false,
+                error: Some(e),
+            })
+        })
+        .filter(|invite| match invite {
+            Ok(steam_id) => seen.insert(steam_id.clone()),
+            Err(_) => true,
+        })
+        .collect();
+
+    // Spawn all invites before awaiting any so they run concurrently
+    let tasks: Vec<_> = invites
+        .into_iter()
+        .map(|invite| {
+            invite.map(|steam_id| {
+                let task = tauri::async_runtime::spawn(invite_friend(steam_id.clone()));
+                (steam_id, task)
+            })
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        let (steam_id, task) = match task {
+            Ok(task) => task,
+            Err(invalid) => {
+                results.push(invalid);
+                continue;
+            }
+        };
+        let (success, error) = match task.await {
+            Ok(Ok(response)) if response.success => (true, None),
+            Ok(Ok(_)) => (false, Some("Invite was not sent".to_string())),
+            Ok(Err(e)) => (false, Some(e.to_string())),
+            Err(e) => (false, Some(format!("Invite task failed: {}", e))),
+        };
+        results.push(InviteResult {
+            steam_id,
+            success,
+            error,
+        });
+    }
+    Ok(results)
+}
+
+/// Kind of lobby change reported by the lobby-event event
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LobbyEventKind {
+    LobbyJoined,
+    LobbyClosed,
+    MemberJoined,
+    MemberLeft,
+}
+
+/// Payload of the lobby-event event
+#[derive(Clone, serde::Serialize)]
+pub struct LobbyEvent {
+    pub kind: LobbyEventKind,
+    pub steam_id: Option<String>,
+    pub lobby_id: String,
+}
+
+/// What we last saw of the current lobby
+#[derive(Default)]
+struct LobbySnapshot {
+    lobby_id: Option<String>,
+    members: BTreeSet<String>,
+}
+
+impl From<GetLobbyInfoResponse> for LobbySnapshot {
+    fn from(info: GetLobbyInfoResponse) -> Self {
+        if !info.is_in_lobby {
+            return LobbySnapshot::default();
+        }
+        LobbySnapshot {
+            lobby_id: Some(info.lobby_id).filter(|id| !id.is_empty()),
+            members: info.members.into_iter().map(|m| m.steam_id).collect(),
+        }
+    }
+}
+
+/// Synthesize lobby events from two successive snapshots
+fn diff_lobby_snapshots(prev: &LobbySnapshot, next: &LobbySnapshot) -> Vec<LobbyEvent> {
+    let mut events = Vec::new();
+
+    if prev.lobby_id != next.lobby_id {
+        if let Some(ref lobby_id) = prev.lobby_id {
+            events.push(LobbyEvent {
+                kind: LobbyEventKind::LobbyClosed,
+                steam_id: None,
+                lobby_id: lobby_id.clone(),
+            });
+        }
+        // The initial roster of a new lobby is not reported as individual joins
+        if let Some(ref lobby_id) = next.lobby_id {
+            events.push(LobbyEvent {
+                kind: LobbyEventKind::LobbyJoined,
+                steam_id: None,
+                lobby_id: lobby_id.clone(),
+            });
+        }
+        return events;
+    }
+
+    if let Some(ref lobby_id) = next.lobby_id {
+        for steam_id in next.members.difference(&prev.members) {
+            events.push(LobbyEvent {
+                kind: LobbyEventKind::MemberJoined,
+                steam_id: Some(steam_id.clone()),
+                lobby_id: lobby_id.clone(),
+            });
+        }
+        for steam_id in prev.members.difference(&next.members) {
+            events.push(LobbyEvent {
+                kind: LobbyEventKind::MemberLeft,
+                steam_id: Some(steam_id.clone()),
+                lobby_id: lobby_id.clone(),
+            });
+        }
+    }
+
+    events
+}
+
+// Background task polling the lobby and emitting change events
+static LOBBY_EVENTS_TASK: Lazy<Mutex<Option<tauri::async_runtime::JoinHandle<()>>>> =
+    Lazy::new(|| Mutex::new(None));
+
+/// Abort the lobby event poller, returning whether one was running
+fn stop_lobby_events_task() -> bool {
+    let task = LOBBY_EVENTS_TASK.lock().unwrap().take();
+    if let Some(ref task) = task {
+        task.abort();
+    }
+    task.is_some()
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(app))]
+async fn subscribe_lobby_events(app: tauri::AppHandle) -> Result<(), CommandError> {
+    // The core has no lobby event stream, so changes are derived by polling
+    let mut snapshot: LobbySnapshot = get_lobby_info().await?.into();
+
+    let task = tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            interval.tick().await;
+            // Transient failures are skipped, the next successful poll catches up
+            let Ok(info) = get_lobby_info().await else {
+                continue;
+            };
+            let next = LobbySnapshot::from(info);
+            for event in diff_lobby_snapshots(&snapshot, &next) {
+                let _ = app.emit("lobby-event", event);
+            }
+            snapshot = next;
+        }
+    });
+
+    // Replace any previous subscription
+    if let Some(previous) = LOBBY_EVENTS_TASK.lock().unwrap().replace(task) {
+        previous.abort();
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn unsubscribe_lobby_events() -> Result<(), CommandError> {
+    stop_lobby_events_task();
+    Ok(())
+}
+
+/// Why a kick_member call failed
+#[derive(serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KickFailureReason {
+    NotHost,
+    MemberLeft,
+    Unknown,
+}
+
+/// Response structure for kick_member command
+#[derive(serde::Serialize)]
+pub struct KickMemberResult {
+    pub success: bool,
+    pub reason: Option<KickFailureReason>,
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn kick_member(steam_id: String) -> Result<KickMemberResult, CommandError> {
+    ensure_writable()?;
+    let steam_id = steam_id.trim().to_string();
+    if steam_id.is_empty() {
+        return Err("steam_id cannot be empty".into());
+    }
+
+    let response = if mock_mode() {
+        mock::kick_member()
+    } else {
+        let result = with_connect_retry(|mut client| {
+            let steam_id = steam_id.clone();
+            async move { client.kick_member(KickMemberRequest { steam_id }).await }
+        })
+        .await;
+        record_core_feature("kick_member", result)?
+    };
+
+    if response.success {
+        return Ok(KickMemberResult {
+            success: true,
+            reason: None,
+        });
+    }
+
+    let reason = match response.error() {
+        KickMemberError::NotHost => KickFailureReason::NotHost,
+        KickMemberError::MemberNotFound => KickFailureReason::MemberLeft,
+        KickMemberError::None => KickFailureReason::Unknown,
+    };
+    Ok(KickMemberResult {
+        success: false,
+        reason: Some(reason),
+    })
+}
+
+async fn fetch_vpn_status() -> Result<GetVpnStatusResponse, CommandError> {
+    if mock_mode() {
+        return Ok(mock::vpn_status());
+    }
+    let result = with_retry(|mut client| async move { client.get_vpn_status(GetVpnStatusRequest {}).await }).await;
+    record_core_feature("vpn_status", result).map(normalize_vpn_status)
+}
+
+/// Fill in what a mismatched core may leave out, so the VPN view renders zeroed stats
+/// instead of nothing
+fn normalize_vpn_status(mut status: GetVpnStatusResponse) -> GetVpnStatusResponse {
+    status.stats = Some(status.stats.unwrap_or_default());
+    status.local_ip = status.local_ip.trim().to_string();
+    status.device_name = status.device_name.trim().to_string();
+    status
+}
+
+/// Virtual interfaces reported by the core. The core drives a single device and its status
+/// and routes carry no per-interface data, so this holds at most one name and the VPN
+/// commands always cover that one device.
+fn vpn_interfaces(status: &GetVpnStatusResponse) -> Vec<String> {
+    let name = status.device_name.trim();
+    if name.is_empty() {
+        Vec::new()
+    } else {
+        vec![name.to_string()]
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_vpn_status() -> Result<GetVpnStatusResponse, CommandError> {
+    fetch_vpn_status().await
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_vpn_routing_table() -> Result<GetVpnRoutingTableResponse, CommandError> {
+    if mock_mode() {
+        return Ok(mock::vpn_routing_table());
+    }
+    record_core_feature(
+        "vpn_routing_table",
+        with_retry(|mut client| async move { client.get_vpn_routing_table(GetVpnRoutingTableRequest {}).await }).await,
+    )
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn list_vpn_interfaces() -> Result<Vec<String>, CommandError> {
+    Ok(vpn_interfaces(&fetch_vpn_status().await?))
+}
+
+/// A relay region the VPN can be routed through
+#[derive(serde::Serialize)]
+pub struct VpnRegionEntry {
+    pub id: String,
+    pub name: String,
+    /// None when the core has no ping estimate
+    pub ping_ms: Option<u32>,
+}
+
+/// Response structure for list_vpn_regions command
+#[derive(serde::Serialize)]
+pub struct VpnRegionsResponse {
+    pub regions: Vec<VpnRegionEntry>,
+    /// None when the core picks the region automatically
+    pub current_region: Option<String>,
+}
+
+/// Older cores don't implement region selection
+fn region_rpc_error(err: CommandError) -> CommandError {
+    match err {
+        CommandError::Rpc { code, .. } if code == tonic::Code::Unimplemented as i32 => {
+            CommandError::Internal("ConnectToolCore does not support VPN region selection".to_string())
+        }
+        other => other,
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn list_vpn_regions() -> Result<VpnRegionsResponse, CommandError> {
+    let response = if mock_mode() {
+        mock::vpn_regions()
+    } else {
+        let result =
+            with_retry(|mut client| async move { client.list_vpn_regions(ListVpnRegionsRequest {}).await }).await;
+        record_core_feature("vpn_regions", result).map_err(region_rpc_error)?
+    };
+
+    let mut regions: Vec<VpnRegionEntry> = response
+        .regions
+        .into_iter()
+        .map(|region| VpnRegionEntry {
+            name: if region.name.is_empty() { region.id.clone() } else { region.name },
+            id: region.id,
+            ping_ms: u32::try_from(region.ping_ms).ok(),
+        })
+        .collect();
+    // Regions without an estimate go last
+    regions.sort_by_key(|region| region.ping_ms.unwrap_or(u32::MAX));
+
+    Ok(VpnRegionsResponse {
+        regions,
+        current_region: Some(response.current_region).filter(|region| !region.is_empty()),
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn set_vpn_region(region: String) -> Result<SetVpnRegionResponse, CommandError> {
+    ensure_writable()?;
+    let region = region.trim().to_string();
+    let available = list_vpn_regions().await?;
+    if !available.regions.iter().any(|entry| entry.id == region) {
+        let ids: Vec<&str> = available.regions.iter().map(|entry| entry.id.as_str()).collect();
+        return Err(format!("Unknown VPN region: {} (available: {})", region, ids.join(", ")).into());
+    }
+
+    if mock_mode() {
+        return Ok(mock::set_vpn_region(&region));
+    }
+    let result = with_connect_retry(|mut client| {
+        let region = region.clone();
+        async move { client.set_vpn_region(SetVpnRegionRequest { region }).await }
+    })
+    .await;
+    record_core_feature("set_vpn_region", result).map_err(region_rpc_error)
+}
+
+// There is deliberately no reconnect_vpn: the core has no RPC that re-establishes the tunnel
+// on its own, and none of the existing ones does it as a side effect. restart_core is the
+// recovery for a wedged VPN until the core grows one.
+
+/// A VPN route with its address in dotted form
+#[derive(serde::Serialize)]
+pub struct VpnRouteEntry {
+    pub name: String,
+    pub virtual_ip: String,
+    pub ip: u32,
+    pub is_local: bool,
+}
+
+/// Normalize a route from the core into the shape shown to the frontend
+fn vpn_route_entry(route: &VpnRoute) -> VpnRouteEntry {
+    let virtual_ip = std::net::Ipv4Addr::from(route.ip).to_string();
+    VpnRouteEntry {
+        // Unnamed peers are shown by address
+        name: if route.name.trim().is_empty() { virtual_ip.clone() } else { route.name.clone() },
+        virtual_ip,
+        ip: route.ip,
+        is_local: route.is_local,
+    }
+}
+
+/// Response structure for get_vpn_routes command
+#[derive(serde::Serialize)]
+pub struct VpnRoutesResponse {
+    /// Sorted by virtual IP
+    pub routes: Vec<VpnRouteEntry>,
+    /// Number of remote peers, excluding the local route
+    pub total_peers: usize,
+    /// Unprocessed response from ConnectToolCore, for debugging
+    pub raw: GetVpnRoutingTableResponse,
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_vpn_routes() -> Result<VpnRoutesResponse, CommandError> {
+    let raw = get_vpn_routing_table().await?;
+
+    let mut routes: Vec<VpnRouteEntry> = raw.routes.iter().map(vpn_route_entry).collect();
+    routes.sort_by_key(|route| route.ip);
+
+    Ok(VpnRoutesResponse {
+        total_peers: routes.iter().filter(|route| !route.is_local).count(),
+        routes,
+        raw,
+    })
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Serialize routes as CSV with a header row, columns matching VpnRouteEntry
+fn vpn_routes_csv(routes: &[VpnRouteEntry]) -> String {
+    let mut csv = String::from("name,virtual_ip,ip,is_local\n");
+    for route in routes {
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            csv_field(&route.name),
+            route.virtual_ip,
+            route.ip,
+            route.is_local
+        ));
+    }
+    csv
+}
+
+/// Routing table as a "json" or "csv" document the frontend can save for a support report
+#[tauri::command]
+#[tracing::instrument]
+async fn export_vpn_routing_table(format: String) -> Result<String, CommandError> {
+    let format = format.trim().to_ascii_lowercase();
+    if format != "json" && format != "csv" {
+        return Err(format!("Unsupported export format: {} (expected json or csv)", format).into());
+    }
+
+    let routes = get_vpn_routes().await?.routes;
+    if format == "csv" {
+        return Ok(vpn_routes_csv(&routes));
+    }
+    serde_json::to_string_pretty(&routes)
+        .map_err(|e| format!("Failed to serialize routing table: {}", e).into())
+}
+
+/// Response structure for get_vpn_stats command
+#[derive(serde::Serialize)]
+pub struct VpnStatsResponse {
+    /// Remote peers, excluding the local route like get_vpn_routes' total_peers
+    pub peer_count: usize,
+    pub total_tx_bytes: u64,
+    pub total_rx_bytes: u64,
+    /// Remote peers sorted by virtual IP, only listed when asked for with include_peers
+    pub connected_peers: Option<Vec<VpnRouteEntry>>,
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_vpn_stats(include_peers: Option<bool>) -> Result<VpnStatsResponse, CommandError> {
+    let (status, table) = tokio::join!(get_vpn_status(), get_vpn_routing_table());
+    let stats = status?.stats.unwrap_or_default();
+    let routes = table?.routes;
+    let remote = || routes.iter().filter(|route| !route.is_local);
+
+    // The totals alone don't need the normalized entries
+    let connected_peers = include_peers.unwrap_or(false).then(|| {
+        let mut peers: Vec<VpnRouteEntry> = remote().map(vpn_route_entry).collect();
+        peers.sort_by_key(|route| route.ip);
+        peers
+    });
+
+    Ok(VpnStatsResponse {
+        peer_count: remote().count(),
+        total_tx_bytes: stats.bytes_sent,
+        total_rx_bytes: stats.bytes_received,
+        connected_peers,
+    })
+}
+
+// Background task forwarding the VPN status stream to the frontend
+static VPN_STATUS_TASK: Lazy<Mutex<Option<tauri::async_runtime::JoinHandle<()>>>> =
+    Lazy::new(|| Mutex::new(None));
+
+/// Abort the VPN status forwarding task, returning whether one was running
+fn stop_vpn_status_task() -> bool {
+    let task = VPN_STATUS_TASK.lock().unwrap().take();
+    if let Some(ref task) = task {
+        task.abort();
+    }
+    task.is_some()
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(app))]
+async fn subscribe_vpn_status(app: tauri::AppHandle) -> Result<(), CommandError> {
+    if mock_mode() {
+        let task = tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(1));
+            loop {
+                interval.tick().await;
+                let _ = app.emit("vpn-status", mock::vpn_status());
+            }
+        });
+        if let Some(previous) = VPN_STATUS_TASK.lock().unwrap().replace(task) {
+            previous.abort();
+        }
+        return Ok(());
+    }
+
+    let mut client = get_client().await?;
+    let result = client
+        .subscribe_vpn_status(SubscribeVpnStatusRequest {})
+        .await
+        .map_err(rpc_error);
+    let mut stream = record_core_feature("vpn_status_stream", result)?.into_inner();
+
+    let task = tauri::async_runtime::spawn(async move {
+        let reason = loop {
+            match stream.message().await {
+                Ok(Some(status)) => {
+                    let _ = app.emit("vpn-status", normalize_vpn_status(status));
+                }
+                Ok(None) => break "VPN status stream closed by ConnectToolCore".to_string(),
+                Err(e) => break rpc_error(e).to_string(),
+            }
+        };
+        let _ = app.emit("vpn-status-ended", reason);
+    });
+
+    // Replace any previous subscription
+    if let Some(previous) = VPN_STATUS_TASK.lock().unwrap().replace(task) {
+        previous.abort();
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn unsubscribe_vpn_status() -> Result<(), CommandError> {
+    stop_vpn_status_task();
+    Ok(())
+}
+
+// ============== Steam Management Commands ==============
+
+#[tauri::command]
+#[tracing::instrument]
+async fn find_steam() -> Result<FindSteamPathResponse, CommandError> {
+    match find_steam_path_with_source() {
+        Some((steam_path, source)) => {
+            let steam_exe = get_steam_exe_path(&steam_path);
+            Ok(FindSteamPathResponse {
+                found: true,
+                steam_path: Some(steam_path.to_string_lossy().to_string()),
+                steam_exe_path: steam_exe.map(|p| p.to_string_lossy().to_string()),
+                source: Some(source),
+                is_steam_deck: is_steam_deck(),
+                message: "Steam found successfully".to_string(),
+            })
+        }
+        None => Ok(FindSteamPathResponse {
+            found: false,
+            steam_path: None,
+            steam_exe_path: None,
+            source: None,
+            is_steam_deck: is_steam_deck(),
+            message: "Steam installation not found".to_string(),
+        }),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn set_steam_path_override(path: String) -> Result<FindSteamPathResponse, CommandError> {
+    let steam_path = PathBuf::from(path.trim());
+    let steam_exe = get_steam_exe_path(&steam_path)
+        .ok_or_else(|| format!("No Steam executable found in {}", steam_path.display()))?;
+
+    modify_settings(|s| s.steam_path_override = Some(steam_path.clone()))?;
+
+    Ok(FindSteamPathResponse {
+        found: true,
+        steam_path: Some(steam_path.to_string_lossy().to_string()),
+        steam_exe_path: Some(steam_exe.to_string_lossy().to_string()),
+        source: Some("override".to_string()),
+        is_steam_deck: is_steam_deck(),
+        message: "Steam path override saved".to_string(),
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn clear_steam_path_override() -> Result<FindSteamPathResponse, CommandError> {
+    modify_settings(|s| s.steam_path_override = None)?;
+
+    // Report what auto-detection finds now
+    find_steam().await
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_steam_running_status() -> Result<GetSteamStatusResponse, CommandError> {
+    let pids = find_steam_pids();
+    let pid = pids.first().copied();
+    Ok(GetSteamStatusResponse {
+        is_running: pid.is_some(),
+        process_id: pid,
+        process_ids: pids,
+        china_mode: pid.and_then(steam_china_mode),
+    })
+}
+
+// Cancelled by cancel_steam_restart, set while a Steam restart is in progress
+static STEAM_RESTART: Lazy<Mutex<Option<CancellationToken>>> = Lazy::new(|| Mutex::new(None));
+
+/// Clears STEAM_RESTART when the restart finishes, however it ends
+struct SteamRestartGuard;
+
+impl SteamRestartGuard {
+    fn acquire() -> Result<(Self, CancellationToken), String> {
+        let mut slot = STEAM_RESTART.lock().unwrap();
+        if slot.is_some() {
+            return Err("Steam restart already in progress".to_string());
+        }
+        let token = CancellationToken::new();
+        *slot = Some(token.clone());
+        Ok((SteamRestartGuard, token))
+    }
+}
+
+impl Drop for SteamRestartGuard {
+    fn drop(&mut self) {
+        *STEAM_RESTART.lock().unwrap() = None;
+    }
+}
+
+/// Response for a restart that failed before Steam was stopped
+fn restart_failed(message: String) -> RestartSteamChinaResponse {
+    RestartSteamChinaResponse {
+        success: false,
+        message,
+        shutdown_result: None,
+        stopped_pids: Vec::new(),
+        cancelled: false,
+    }
+}
+
+/// Restart Steam in steps, checking for cancel_steam_restart before each one
+async fn restart_steam(args: &[&str]) -> RestartSteamChinaResponse {
+    let (_guard, token) = match SteamRestartGuard::acquire() {
+        Ok(acquired) => acquired,
+        Err(e) => return restart_failed(e),
+    };
+
+    // Find Steam path
+    let steam_path = match find_steam_path() {
+        Some(path) => path,
+        None => return restart_failed("Steam installation not found".to_string()),
+    };
+
+    // Get Steam executable path
+    let steam_exe = match get_steam_exe_path(&steam_path) {
+        Some(exe) => exe,
+        None => return restart_failed("Steam executable not found".to_string()),
+    };
+
+    if token.is_cancelled() {
+        return RestartSteamChinaResponse {
+            cancelled: true,
+            ..restart_failed("Steam restart cancelled, Steam was not stopped".to_string())
+        };
+    }
+
+    // Stop Steam if it is running, off the async runtime since it waits for Steam to exit
+    let stop_exe = steam_exe.clone();
+    let (shutdown_result, stopped_pids) =
+        match tauri::async_runtime::spawn_blocking(move || stop_steam(&stop_exe)).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => return restart_failed(format!("Failed to stop Steam: {}", e)),
+            Err(e) => return restart_failed(format!("Failed to stop Steam: {}", e)),
+        };
+
+    if token.is_cancelled() {
+        return RestartSteamChinaResponse {
+            success: false,
+            message: "Steam restart cancelled, Steam was stopped but not restarted".to_string(),
+            shutdown_result: Some(shutdown_result),
+            stopped_pids,
+            cancelled: true,
+        };
+    }
+
+    // Start Steam with the requested arguments
+    match start_steam(&steam_exe, args).await {
+        Ok(()) => RestartSteamChinaResponse {
+            success: true,
+            message: if args.is_empty() {
+                "Steam started".to_string()
+            } else {
+                format!("Steam started with {} parameter", args.join(" "))
+            },
+            shutdown_result: Some(shutdown_result),
+            stopped_pids,
+            cancelled: false,
+        },
+        Err(e) => RestartSteamChinaResponse {
+            success: false,
+            message: e,
+            shutdown_result: Some(shutdown_result),
+            stopped_pids,
+            cancelled: false,
+        },
+    }
+}
+
+/// Response structure for restart_steam_china_dryrun command
+#[derive(serde::Serialize)]
+pub struct RestartSteamPlan {
+    /// Whether the restart would go ahead
+    pub can_restart: bool,
+    pub steam_path: Option<String>,
+    pub steam_exe_path: Option<String>,
+    /// Running Steam process that would be stopped first
+    pub running_pid: Option<u32>,
+    pub will_stop_steam: bool,
+    /// Whether a graceful `-shutdown` is attempted before force-killing
+    pub graceful_shutdown_supported: bool,
+    pub launch_args: Vec<String>,
+    pub message: String,
+}
+
+/// Work out what restart_steam would do with the given arguments, without side effects
+fn plan_steam_restart(args: &[&str]) -> RestartSteamPlan {
+    let launch_args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    let steam_path = find_steam_path();
+    let steam_exe = steam_path.as_deref().and_then(get_steam_exe_path);
+    let running_pid = is_steam_running();
+
+    let message = match (&steam_path, &steam_exe) {
+        (None, _) => "Steam installation not found".to_string(),
+        (Some(_), None) => "Steam executable not found".to_string(),
+        (Some(_), Some(exe)) => {
+            let launch = if launch_args.is_empty() {
+                format!("start {}", exe.display())
+            } else {
+                format!("start {} with {}", exe.display(), launch_args.join(" "))
+            };
+            match running_pid {
+                Some(pid) => format!("Would stop Steam (PID: {}) and {}", pid, launch),
+                None => format!("Would {}", launch),
+            }
+        }
+    };
+
+    RestartSteamPlan {
+        can_restart: steam_exe.is_some(),
+        steam_path: steam_path.map(|p| p.to_string_lossy().to_string()),
+        steam_exe_path: steam_exe.map(|p| p.to_string_lossy().to_string()),
+        running_pid,
+        will_stop_steam: running_pid.is_some(),
+        graceful_shutdown_supported: !cfg!(target_os = "macos"),
+        launch_args,
+        message,
+    }
+}
+
+/// Look for Steam China client markers in an install: package manifests and files
+/// named after steamchina. None if the package directory can't be read.
+fn has_steam_china_markers(steam_path: &Path) -> Option<bool> {
+    let package_dir = get_steam_data_dir(steam_path).join("package");
+    let entries = std::fs::read_dir(&package_dir).ok()?;
+    Some(entries.flatten().any(|entry| {
+        entry
+            .file_name()
+            .to_string_lossy()
+            .to_ascii_lowercase()
+            .contains("steamchina")
+    }))
+}
+
+/// Best-effort hint whether this Steam install can use -steamchina, None when it can't be told
+#[tauri::command]
+#[tracing::instrument]
+async fn steam_china_supported() -> Result<Option<bool>, CommandError> {
+    // A client already running in China mode settles it
+    if is_steam_running().and_then(steam_china_mode) == Some(true) {
+        return Ok(Some(true));
+    }
+    Ok(find_steam_path().and_then(|steam_path| has_steam_china_markers(&steam_path)))
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn restart_steam_china_dryrun() -> Result<RestartSteamPlan, CommandError> {
+    Ok(plan_steam_restart(&["-steamchina"]))
+}
+
+// How long a token from request_steam_restart_token stays valid
+const STEAM_RESTART_TOKEN_TTL: Duration = Duration::from_secs(30);
+
+// The outstanding restart confirmation token and when it was issued
+static STEAM_RESTART_TOKEN: Lazy<Mutex<Option<(String, std::time::Instant)>>> =
+    Lazy::new(|| Mutex::new(None));
+
+/// Response structure for request_steam_restart_token command
+#[derive(serde::Serialize)]
+pub struct SteamRestartToken {
+    pub token: String,
+    pub expires_in_ms: u64,
+}
+
+/// Issue a single-use token that a Steam restart command must be called with,
+/// replacing any earlier token
+#[tauri::command]
+#[tracing::instrument]
+async fn request_steam_restart_token() -> Result<SteamRestartToken, CommandError> {
+    use rand::Rng;
+
+    let token: String = rand::thread_rng()
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+    *STEAM_RESTART_TOKEN.lock().unwrap() = Some((token.clone(), std::time::Instant::now()));
+    Ok(SteamRestartToken {
+        token,
+        expires_in_ms: STEAM_RESTART_TOKEN_TTL.as_millis() as u64,
+    })
+}
+
+/// Consume the restart confirmation token, failing unless it matches and hasn't expired
+fn consume_steam_restart_token(token: &str) -> Result<(), String> {
+    let issued = STEAM_RESTART_TOKEN.lock().unwrap().take();
+    match issued {
+        Some((expected, issued_at))
+            if expected == token && issued_at.elapsed() <= STEAM_RESTART_TOKEN_TTL =>
+        {
+            Ok(())
+        }
+        _ => Err("Invalid or expired restart confirmation token, request a new one".to_string()),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(token))]
+async fn restart_steam_china(token: String) -> Result<RestartSteamChinaResponse, CommandError> {
+    ensure_writable()?;
+    consume_steam_restart_token(&token)?;
+    Ok(restart_steam(&["-steamchina"]).await)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(token))]
+async fn restart_steam_normal(token: String) -> Result<RestartSteamChinaResponse, CommandError> {
+    ensure_writable()?;
+    consume_steam_restart_token(&token)?;
+    Ok(restart_steam(&[]).await)
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn cancel_steam_restart() -> Result<bool, CommandError> {
+    // Takes effect at the next step, a running shutdown is not interrupted
+    Ok(match STEAM_RESTART.lock().unwrap().as_ref() {
+        Some(token) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_steam_libraries() -> Result<Vec<SteamLibrary>, CommandError> {
+    let steam_path = find_steam_path().ok_or("Steam installation not found")?;
+    let vdf_path = get_steam_data_dir(&steam_path).join("steamapps").join("libraryfolders.vdf");
+
+    let content = std::fs::read_to_string(&vdf_path)
+        .map_err(|e| format!("Failed to read {}: {}", vdf_path.display(), e))?;
+
+    // Skip libraries that were removed or live on a disconnected drive
+    Ok(parse_library_folders(&content)?
+        .into_iter()
+        .filter(|lib| Path::new(&lib.path).exists())
+        .collect())
+}
+
+/// Accounts known to the local Steam client, empty if none has logged in yet
+#[tauri::command]
+#[tracing::instrument]
+async fn get_steam_accounts() -> Result<Vec<SteamAccount>, CommandError> {
+    let steam_path = find_steam_path().ok_or("Steam installation not found")?;
+    let vdf_path = get_steam_data_dir(&steam_path).join("config").join("loginusers.vdf");
+
+    let content = match std::fs::read_to_string(&vdf_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", vdf_path.display(), e).into()),
+    };
+    Ok(parse_login_users(&content)?)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(app))]
+async fn open_steam_folder(app: tauri::AppHandle) -> Result<(), CommandError> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let steam_path = find_steam_path().ok_or("Steam installation not found")?;
+    app.opener()
+        .open_path(steam_path.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("Failed to open {}: {}", steam_path.display(), e))?;
+    Ok(())
+}
+
+// ============== End Steam Management Commands ==============
+
+// ============== Firewall Management ==============
+
+/// State of a single firewall profile
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct FirewallProfile {
+    pub name: String,
+    pub enabled: bool,
+}
+
+/// Response structure for firewall status
+#[derive(serde::Serialize)]
+pub struct FirewallStatusResponse {
+    pub domain_enabled: bool,
+    pub private_enabled: bool,
+    pub public_enabled: bool,
+    /// Every profile reported by the system, including non-standard ones
+    pub profiles: Vec<FirewallProfile>,
+    pub message: String,
+}
+
+/// Response structure for firewall toggle
+#[derive(serde::Serialize)]
+pub struct FirewallToggleResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+/// Stable classification of firewall failures, so the UI can tell "run as administrator"
+/// apart from "managed by your organization"
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FirewallErrorCode {
+    AccessDenied,
+    BlockedByPolicy,
+    Unknown,
+}
+
+/// Classify the stderr of a failed firewall command. Policy is checked first because
+/// policy failures often mention access being denied as well.
+fn classify_firewall_error(raw: &str) -> FirewallErrorCode {
+    const POLICY_SIGNATURES: &[&str] = &[
+        "group policy",
+        "组策略",
+        "群組原則",
+        "gruppenrichtlinie",
+        "stratégie de groupe",
+        "managed by your organization",
+    ];
+    // Localized Windows messages are matched too, the HRESULT covers the remaining languages
+    const ACCESS_DENIED_SIGNATURES: &[&str] = &[
+        "access is denied",
+        "拒绝访问",
+        "存取被拒",
+        "zugriff verweigert",
+        "accès refusé",
+        "acceso denegado",
+        "アクセスが拒否されました",
+        "отказано в доступе",
+        "0x80070005",
+        "permissiondenied",
+        "not authorized",
+        "requires administrator",
+        "user canceled",
+    ];
+
+    let raw = raw.to_lowercase();
+    if POLICY_SIGNATURES.iter().any(|signature| raw.contains(signature)) {
+        FirewallErrorCode::BlockedByPolicy
+    } else if ACCESS_DENIED_SIGNATURES.iter().any(|signature| raw.contains(signature)) {
+        FirewallErrorCode::AccessDenied
+    } else {
+        FirewallErrorCode::Unknown
+    }
+}
+
+/// Wrap a firewall failure in a CommandError carrying its classification
+fn firewall_error(raw: String) -> CommandError {
+    CommandError::Firewall {
+        code: classify_firewall_error(&raw),
+        raw,
+    }
+}
+
+/// Firewall state saved before the app disables the firewall
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct FirewallSnapshot {
+    profiles: Vec<FirewallProfile>,
+    /// Seconds since the Unix epoch
+    taken_at: u64,
+}
+
+/// Response structure for restore_firewall command
+#[derive(serde::Serialize)]
+pub struct RestoreFirewallResponse {
+    pub success: bool,
+    pub message: String,
+    /// Set when reapplying the snapshot failed
+    pub error_code: Option<FirewallErrorCode>,
+    /// When the restored snapshot was taken, in seconds since the Unix epoch
+    pub snapshot_taken_at: Option<u64>,
+}
+
+/// Response structure for core status
+#[derive(serde::Serialize)]
+pub struct CoreStatusResponse {
+    pub is_running: bool,
+    pub pid: Option<u32>,
+    /// Whether the running core was started by this app
+    pub managed: bool,
+    /// Only known for a managed core
+    pub uptime_secs: Option<u64>,
+    /// RFC 3339 start time, only known for a managed core
+    pub started_at: Option<String>,
+    pub message: String,
+}
+
+/// Response structure for core control
+#[derive(serde::Serialize)]
+pub struct CoreControlResponse {
+    pub success: bool,
+    pub is_running: bool,
+    pub pid: Option<u32>,
+    pub message: String,
+}
+
+/// Response structure for ping_core command
+#[derive(serde::Serialize)]
+pub struct PingCoreResponse {
+    pub reachable: bool,
+    pub latency_ms: Option<u64>,
+    pub message: String,
+}
+
+// Display name of the firewall rules created for ConnectToolCore
+#[cfg(windows)]
+const CORE_FIREWALL_RULE_NAME: &str = "ConnectToolCore";
+
+/// Run a PowerShell command without showing a console window
+#[cfg(windows)]
+fn run_powershell(cmd: &str) -> Result<std::process::Output, String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    Command::new("powershell")
+        .args(["-Command", cmd])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to execute PowerShell: {}", e))
+}
+
+// Upper bound for firewall PowerShell calls, which can hang on policy-restricted machines
+#[cfg(windows)]
+const FIREWALL_POWERSHELL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Run a firewall PowerShell command, killing it if it runs past FIREWALL_POWERSHELL_TIMEOUT
+#[cfg(windows)]
+fn run_firewall_powershell(cmd: &str) -> Result<std::process::Output, String> {
+    use std::io::Read;
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let mut child = Command::new("powershell")
+        .args(["-Command", cmd])
+        .creation_flags(CREATE_NO_WINDOW)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to execute PowerShell: {}", e))?;
+
+    // Drain the pipes on their own threads so a full pipe can't stall the process
+    fn drain(pipe: Option<impl Read + Send + 'static>) -> Option<std::thread::JoinHandle<Vec<u8>>> {
+        pipe.map(|mut pipe| {
+            std::thread::spawn(move || {
+                let mut buf = Vec::new();
+                let _ = pipe.read_to_end(&mut buf);
+                buf
+            })
+        })
+    }
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = std::time::Instant::now() + FIREWALL_POWERSHELL_TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if std::time::Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err("firewall query timed out".to_string());
+            }
+            Err(e) => return Err(format!("Failed to wait for PowerShell: {}", e)),
+        }
+    };
+
+    let collect = |handle: Option<std::thread::JoinHandle<Vec<u8>>>| {
+        handle.and_then(|handle| handle.join().ok()).unwrap_or_default()
+    };
+    Ok(std::process::Output {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    })
+}
+
+/// Quote a string as a PowerShell single-quoted literal
+#[cfg(windows)]
+fn powershell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Get Windows Firewall status for all profiles
+#[cfg(windows)]
+fn get_firewall_status_windows() -> Result<FirewallStatusResponse, String> {
+    let output = run_firewall_powershell(
+        "Get-NetFirewallProfile | Select-Object -Property Name, Enabled | ConvertTo-Json"
+    )?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("PowerShell command failed: {}", stderr));
+    }
+
+    parse_firewall_profiles_json(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Read a profile's Enabled value, which PowerShell emits as a bool, a GpoBoolean number
+/// or, depending on the version, a "True"/"False" string
+#[cfg(any(windows, test))]
+fn parse_firewall_enabled(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Bool(enabled) => *enabled,
+        serde_json::Value::Number(n) => n.as_i64().is_some_and(|n| n != 0),
+        serde_json::Value::String(s) => {
+            let s = s.trim();
+            s.eq_ignore_ascii_case("true") || s == "1"
+        }
+        _ => false,
+    }
+}
+
+/// Parse the output of a PowerShell command piped to ConvertTo-Json, which is an array
+/// of objects or, when only one object is returned, the object itself
+#[cfg(any(windows, test))]
+fn parse_powershell_json_list(json: &str, what: &str) -> Result<Vec<serde_json::Value>, String> {
+    let parsed: serde_json::Value =
+        serde_json::from_str(json.trim()).map_err(|e| format!("Failed to parse {}: {}", what, e))?;
+    match parsed {
+        serde_json::Value::Array(items) => Ok(items),
+        item @ serde_json::Value::Object(_) => Ok(vec![item]),
+        other => Err(format!("Unexpected {} output: {}", what, other)),
+    }
+}
+
+/// Parse the output of Get-NetFirewallProfile | ConvertTo-Json
+#[cfg(any(windows, test))]
+fn parse_firewall_profiles_json(json: &str) -> Result<FirewallStatusResponse, String> {
+    let profiles = parse_powershell_json_list(json, "firewall status")?;
+    
+    let mut domain_enabled = false;
+    let mut private_enabled = false;
+    let mut public_enabled = false;
+    let mut profile_list = Vec::new();
+    
+    for profile in &profiles {
+        let name = profile.get("Name").and_then(|v| v.as_str()).unwrap_or("");
+        let enabled = profile.get("Enabled").is_some_and(parse_firewall_enabled);
+        
+        match name {
+            "Domain" => domain_enabled = enabled,
+            "Private" => private_enabled = enabled,
+            "Public" => public_enabled = enabled,
+            _ => {}
+        }
+        
+        profile_list.push(FirewallProfile {
+            name: name.to_string(),
+            enabled,
+        });
+    }
+    
+    Ok(FirewallStatusResponse {
+        domain_enabled,
+        private_enabled,
+        public_enabled,
+        profiles: profile_list,
+        message: "Firewall status retrieved successfully".to_string(),
+    })
+}
+
+// Profiles accepted by Set-NetFirewallProfile
+const FIREWALL_PROFILES: [&str; 3] = ["Domain", "Private", "Public"];
+
+/// Match a user-supplied profile name against the known profiles
+fn normalize_firewall_profile(profile: &str) -> Result<&'static str, String> {
+    FIREWALL_PROFILES
+        .iter()
+        .find(|known| known.eq_ignore_ascii_case(profile.trim()))
+        .copied()
+        .ok_or_else(|| {
+            format!(
+                "Unknown firewall profile \"{}\", expected one of: {}",
+                profile,
+                FIREWALL_PROFILES.join(", ")
+            )
+        })
+}
+
+/// Set Windows Firewall status for the given profiles
+#[cfg(windows)]
+fn set_firewall_status_windows(profiles: &[&str], enabled: bool) -> Result<FirewallToggleResponse, String> {
+    let state = if enabled { "True" } else { "False" };
+    let cmd = format!(
+        "Set-NetFirewallProfile -Profile {} -Enabled {}",
+        profiles.join(","),
+        state
+    );
+    
+    let output = run_firewall_powershell(&cmd)?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to set firewall status: {}", stderr));
+    }
+    
+    let action = if enabled { "enabled" } else { "disabled" };
+    let message = if profiles.len() == FIREWALL_PROFILES.len() {
+        format!("Windows Firewall {} successfully", action)
+    } else {
+        format!("Windows Firewall {} profile {} successfully", profiles.join(", "), action)
+    };
+    Ok(FirewallToggleResponse {
+        success: true,
+        message,
+    })
+}
+
+/// Allow ConnectToolCore through the Windows Firewall in both directions
+#[cfg(windows)]
+fn add_core_firewall_rule_windows() -> Result<FirewallToggleResponse, String> {
+    let core_path = get_core_executable_path();
+    if !core_path.exists() {
+        return Err(format!("ConnectToolCore not found at: {}", core_path.display()));
+    }
+
+    let name = powershell_quote(CORE_FIREWALL_RULE_NAME);
+    let program = powershell_quote(&core_path.to_string_lossy());
+    // Remove any previous rules first so repeated calls don't stack duplicates
+    let cmd = format!(
+        "Remove-NetFirewallRule -DisplayName {name} -ErrorAction SilentlyContinue; \
+         New-NetFirewallRule -DisplayName {name} -Direction Inbound -Program {program} -Action Allow | Out-Null; \
+         New-NetFirewallRule -DisplayName {name} -Direction Outbound -Program {program} -Action Allow | Out-Null"
+    );
+
+    let output = run_firewall_powershell(&cmd)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to add firewall rule: {}", stderr));
+    }
+
+    Ok(FirewallToggleResponse {
+        success: true,
+        message: format!("Firewall rule added for {}", core_path.display()),
+    })
+}
+
+/// Delete the ConnectToolCore firewall rules
+#[cfg(windows)]
+fn remove_core_firewall_rule_windows() -> Result<FirewallToggleResponse, String> {
+    let cmd = format!(
+        "Remove-NetFirewallRule -DisplayName {} -ErrorAction SilentlyContinue",
+        powershell_quote(CORE_FIREWALL_RULE_NAME)
+    );
+
+    let output = run_firewall_powershell(&cmd)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to remove firewall rule: {}", stderr));
+    }
+
+    Ok(FirewallToggleResponse {
+        success: true,
+        message: "Firewall rule for ConnectToolCore removed".to_string(),
+    })
+}
+
+/// Response structure for core_firewall_rule_exists command
+#[derive(serde::Serialize)]
+pub struct FirewallRuleStatus {
+    pub exists: bool,
+    /// Whether every rule with the name is enabled, None when there is no rule
+    pub enabled: Option<bool>,
+}
+
+/// Look up the ConnectToolCore firewall rules
+#[cfg(windows)]
+fn get_core_firewall_rule_windows() -> Result<FirewallRuleStatus, String> {
+    // SilentlyContinue turns "No MSFT_NetFirewallRule objects found" into empty output
+    let cmd = format!(
+        "Get-NetFirewallRule -DisplayName {} -ErrorAction SilentlyContinue | ForEach-Object {{ $_.Enabled.ToString() }}",
+        powershell_quote(CORE_FIREWALL_RULE_NAME)
+    );
+
+    let output = run_firewall_powershell(&cmd)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to query firewall rule: {}", stderr));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let states: Vec<bool> = stdout
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.eq_ignore_ascii_case("True"))
+        .collect();
+    Ok(FirewallRuleStatus {
+        exists: !states.is_empty(),
+        enabled: (!states.is_empty()).then(|| states.iter().all(|&enabled| enabled)),
+    })
+}
+
+#[cfg(not(windows))]
+fn get_core_firewall_rule_windows() -> Result<FirewallRuleStatus, String> {
+    Err("Firewall management is only supported on Windows".to_string())
+}
+
+#[cfg(not(windows))]
+fn add_core_firewall_rule_windows() -> Result<FirewallToggleResponse, String> {
+    Err("Firewall management is only supported on Windows".to_string())
+}
+
+#[cfg(not(windows))]
+fn remove_core_firewall_rule_windows() -> Result<FirewallToggleResponse, String> {
+    Err("Firewall management is only supported on Windows".to_string())
+}
+
+// Command line tool controlling the macOS Application Layer Firewall
+#[cfg(target_os = "macos")]
+const SOCKETFILTERFW_PATH: &str = "/usr/libexec/ApplicationFirewall/socketfilterfw";
+
+/// Get the macOS Application Layer Firewall status
+#[cfg(target_os = "macos")]
+fn get_firewall_status_macos() -> Result<FirewallStatusResponse, String> {
+    let output = Command::new(SOCKETFILTERFW_PATH)
+        .arg("--getglobalstate")
+        .output()
+        .map_err(|e| format!("Failed to execute socketfilterfw: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("socketfilterfw failed: {}", stderr));
+    }
+
+    // e.g. "Firewall is enabled. (State = 1)", state 2 means blocking all incoming connections
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let enabled = match stdout.split("State = ").nth(1) {
+        Some(rest) => !rest.trim_start().starts_with('0'),
+        None => stdout.contains("enabled"),
+    };
+
+    // The Application Firewall has a single global state, so every profile mirrors it
+    Ok(FirewallStatusResponse {
+        domain_enabled: enabled,
+        private_enabled: enabled,
+        public_enabled: enabled,
+        profiles: FIREWALL_PROFILES
+            .iter()
+            .map(|name| FirewallProfile {
+                name: name.to_string(),
+                enabled,
+            })
+            .collect(),
+        message: "Firewall status retrieved successfully".to_string(),
+    })
+}
+
+/// Set the macOS Application Layer Firewall state, prompting for admin rights
+#[cfg(target_os = "macos")]
+fn set_firewall_status_macos(enabled: bool) -> Result<FirewallToggleResponse, String> {
+    let state = if enabled { "on" } else { "off" };
+    let script = format!(
+        "do shell script \"{} --setglobalstate {}\" with administrator privileges",
+        SOCKETFILTERFW_PATH, state
+    );
+
+    let output = Command::new("osascript")
+        .args(["-e", &script])
+        .output()
+        .map_err(|e| format!("Failed to execute osascript: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to set firewall status: {}", stderr));
+    }
+
+    let action = if enabled { "enabled" } else { "disabled" };
+    Ok(FirewallToggleResponse {
+        success: true,
+        message: format!("macOS Firewall {} successfully", action),
+    })
+}
+
+/// Firewall front-ends supported on Linux
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy)]
+enum LinuxFirewallTool {
+    Ufw,
+    Firewalld,
+}
+
+/// Find which firewall tool is installed, preferring ufw
+#[cfg(target_os = "linux")]
+fn detect_linux_firewall_tool() -> Result<LinuxFirewallTool, String> {
+    let installed = |tool: &str| {
+        Command::new("which")
+            .arg(tool)
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    };
+
+    if installed("ufw") {
+        Ok(LinuxFirewallTool::Ufw)
+    } else if installed("firewall-cmd") {
+        Ok(LinuxFirewallTool::Firewalld)
+    } else {
+        Err("No supported firewall tool found (expected ufw or firewalld)".to_string())
+    }
+}
+
+/// Get the Linux firewall status from ufw or firewalld
+#[cfg(target_os = "linux")]
+fn get_firewall_status_linux() -> Result<FirewallStatusResponse, String> {
+    let (enabled, tool_name) = match detect_linux_firewall_tool()? {
+        LinuxFirewallTool::Ufw => {
+            let output = Command::new("ufw")
+                .arg("status")
+                .output()
+                .map_err(|e| format!("Failed to execute ufw: {}", e))?;
+            let enabled = if output.status.success() {
+                // "Status: active" or "Status: inactive"
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .any(|line| line.trim() == "Status: active")
+            } else {
+                // ufw status needs root, the config file is world-readable
+                std::fs::read_to_string("/etc/ufw/ufw.conf")
+                    .map_err(|e| format!("Failed to read ufw status: {}", e))?
+                    .lines()
+                    .any(|line| line.trim().eq_ignore_ascii_case("ENABLED=yes"))
+            };
+            (enabled, "ufw")
+        }
+        LinuxFirewallTool::Firewalld => {
+            // Prints "running" or "not running", no root needed
+            let output = Command::new("firewall-cmd")
+                .arg("--state")
+                .output()
+                .map_err(|e| format!("Failed to execute firewall-cmd: {}", e))?;
+            let enabled = String::from_utf8_lossy(&output.stdout).trim() == "running";
+            (enabled, "firewalld")
+        }
+    };
+
+    // Linux firewalls have a single global state, so every profile mirrors it
+    Ok(FirewallStatusResponse {
+        domain_enabled: enabled,
+        private_enabled: enabled,
+        public_enabled: enabled,
+        profiles: FIREWALL_PROFILES
+            .iter()
+            .map(|name| FirewallProfile {
+                name: name.to_string(),
+                enabled,
+            })
+            .collect(),
+        message: format!("Firewall status retrieved from {}", tool_name),
+    })
+}
+
+/// Enable or disable the Linux firewall, prompting for admin rights through polkit
+#[cfg(target_os = "linux")]
+fn set_firewall_status_linux(enabled: bool) -> Result<FirewallToggleResponse, String> {
+    let (args, tool_name): (&[&str], &str) = match detect_linux_firewall_tool()? {
+        // --force skips the interactive "may disrupt existing ssh connections" prompt
+        LinuxFirewallTool::Ufw if enabled => (&["ufw", "--force", "enable"], "ufw"),
+        LinuxFirewallTool::Ufw => (&["ufw", "disable"], "ufw"),
+        // firewall-cmd can't turn firewalld off, so the service itself is toggled
+        LinuxFirewallTool::Firewalld if enabled => (&["systemctl", "start", "firewalld"], "firewalld"),
+        LinuxFirewallTool::Firewalld => (&["systemctl", "stop", "firewalld"], "firewalld"),
+    };
+
+    let output = Command::new("pkexec")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to execute pkexec: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to set firewall status: {}", stderr));
+    }
+
+    let action = if enabled { "enabled" } else { "disabled" };
+    Ok(FirewallToggleResponse {
+        success: true,
+        message: format!("Firewall ({}) {} successfully", tool_name, action),
+    })
+}
+
+/// Cross-platform firewall status
+fn get_system_firewall_status() -> Result<FirewallStatusResponse, String> {
+    #[cfg(windows)]
+    return get_firewall_status_windows();
+
+    #[cfg(target_os = "macos")]
+    return get_firewall_status_macos();
+
+    #[cfg(target_os = "linux")]
+    return get_firewall_status_linux();
+
+    #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+    return Err("Firewall management is not supported on this platform".to_string());
+}
+
+/// Cross-platform firewall toggle for the given profiles
+fn set_system_firewall_status(profiles: &[&str], enabled: bool) -> Result<FirewallToggleResponse, String> {
+    #[cfg(windows)]
+    return set_firewall_status_windows(profiles, enabled);
+
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    {
+        if profiles.len() != FIREWALL_PROFILES.len() {
+            return Err("This firewall has no separate profiles, toggle it as a whole".to_string());
+        }
+
+        #[cfg(target_os = "macos")]
+        return set_firewall_status_macos(enabled);
+
+        #[cfg(target_os = "linux")]
+        return set_firewall_status_linux(enabled);
+    }
+
+    #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+    {
+        let _ = (profiles, enabled);
+        Err("Firewall management is not supported on this platform".to_string())
+    }
+}
+
+/// Run a firewall operation on the blocking pool. The firewall tools are waited on
+/// synchronously, for up to FIREWALL_POWERSHELL_TIMEOUT on Windows, which must not stall
+/// the async runtime other commands are running on.
+async fn run_firewall_blocking<T: Send + 'static>(
+    op: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(op)
+        .await
+        .map_err(|e| format!("Firewall task failed: {}", e))?
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_firewall_status() -> Result<FirewallStatusResponse, CommandError> {
+    run_firewall_blocking(get_system_firewall_status).await.map_err(firewall_error)
+}
+
+/// Save the current per-profile firewall state so it can be restored later
+fn snapshot_firewall_state() -> Result<(), String> {
+    let status = get_system_firewall_status()?;
+
+    // An all-disabled state carries no information, keep the earlier snapshot instead
+    if !status.profiles.iter().any(|p| p.enabled) {
+        return Ok(());
+    }
+
+    let snapshot = FirewallSnapshot {
+        profiles: status.profiles,
+        taken_at: unix_timestamp(),
+    };
+    modify_settings(|s| s.firewall_snapshot = Some(snapshot))
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn set_firewall(enabled: bool) -> Result<FirewallToggleResponse, CommandError> {
+    ensure_writable()?;
+    run_firewall_blocking(move || {
+        if !enabled {
+            // Best effort, failing to snapshot should not block the toggle
+            let _ = snapshot_firewall_state();
+        }
+        set_system_firewall_status(&FIREWALL_PROFILES, enabled)
+    })
+    .await
+    .map_err(firewall_error)
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn restore_firewall() -> Result<RestoreFirewallResponse, CommandError> {
+    ensure_writable()?;
+    let snapshot = match read_settings(|s| s.firewall_snapshot.clone()) {
+        Some(snapshot) => snapshot,
+        None => {
+            return Ok(RestoreFirewallResponse {
+                success: false,
+                message: "No saved firewall state to restore".to_string(),
+                error_code: None,
+                snapshot_taken_at: None,
+            });
+        }
+    };
+
+    let mut enabled = Vec::new();
+    let mut disabled = Vec::new();
+    for profile in &snapshot.profiles {
+        // Only profiles Set-NetFirewallProfile understands can be reapplied
+        if let Ok(name) = normalize_firewall_profile(&profile.name) {
+            if profile.enabled {
+                enabled.push(name);
+            } else {
+                disabled.push(name);
+            }
+        }
+    }
+
+    for (profiles, state) in [(enabled, true), (disabled, false)] {
+        if profiles.is_empty() {
+            continue;
+        }
+        if let Err(e) = run_firewall_blocking(move || set_system_firewall_status(&profiles, state)).await {
+            return Ok(RestoreFirewallResponse {
+                success: false,
+                error_code: Some(classify_firewall_error(&e)),
+                message: e,
+                snapshot_taken_at: Some(snapshot.taken_at),
+            });
+        }
+    }
+
+    modify_settings(|s| s.firewall_snapshot = None)?;
+    Ok(RestoreFirewallResponse {
+        success: true,
+        message: "Firewall state restored".to_string(),
+        error_code: None,
+        snapshot_taken_at: Some(snapshot.taken_at),
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn set_firewall_profile(profile: String, enabled: bool) -> Result<FirewallToggleResponse, CommandError> {
+    ensure_writable()?;
+    let profile = normalize_firewall_profile(&profile)?;
+    run_firewall_blocking(move || set_system_firewall_status(&[profile], enabled))
+        .await
+        .map_err(firewall_error)
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn add_core_firewall_rule() -> Result<FirewallToggleResponse, CommandError> {
+    ensure_writable()?;
+    run_firewall_blocking(add_core_firewall_rule_windows).await.map_err(firewall_error)
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn core_firewall_rule_exists() -> Result<FirewallRuleStatus, CommandError> {
+    run_firewall_blocking(get_core_firewall_rule_windows).await.map_err(firewall_error)
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn remove_core_firewall_rule() -> Result<FirewallToggleResponse, CommandError> {
+    ensure_writable()?;
+    run_firewall_blocking(remove_core_firewall_rule_windows).await.map_err(firewall_error)
+}
+
+// ============== End Firewall Management ==============
+
+// ============== Elevation ==============
+
+/// Response structure for is_elevated command
+#[derive(serde::Serialize)]
+pub struct ElevationStatus {
+    pub elevated: bool,
+}
+
+/// Check whether the app runs with administrator rights on Windows
+#[cfg(windows)]
+fn is_process_elevated() -> bool {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    // `net session` only succeeds for elevated processes
+    Command::new("net")
+        .arg("session")
+        .creation_flags(CREATE_NO_WINDOW)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Check whether the app runs as root on Unix
+#[cfg(unix)]
+fn is_process_elevated() -> bool {
+    Command::new("id")
+        .arg("-u")
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "0")
+        .unwrap_or(false)
+}
+
+#[cfg(not(any(windows, unix)))]
+fn is_process_elevated() -> bool {
+    false
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn is_elevated() -> Result<ElevationStatus, CommandError> {
+    Ok(ElevationStatus {
+        elevated: is_process_elevated(),
+    })
+}
+
+// ============== End Elevation ==============
+
+// ============== ConnectToolCore Management ==============
+
+// File name of the ConnectToolCore executable
+#[cfg(windows)]
+const CORE_EXECUTABLE_NAME: &str = "ConnectToolCore.exe";
+#[cfg(not(windows))]
+const CORE_EXECUTABLE_NAME: &str = "ConnectToolCore";
+
+/// User-specified core executable, e.g. a locally built core during development
+fn core_path_override() -> Option<PathBuf> {
+    read_settings(|s| s.core_path_override.clone())
+}
+
+/// Get the path to ConnectToolCore executable
+fn get_core_executable_path() -> PathBuf {
+    // No fallback if the override goes missing, silently running a different core would be confusing
+    if let Some(path) = core_path_override() {
+        return path;
+    }
+
+    let candidates = core_search_paths();
+    // When nothing matches, report the primary location in errors
+    candidates
+        .iter()
+        .find(|path| path.is_file())
+        .or(candidates.first())
+        .cloned()
+        .unwrap_or_else(|| PathBuf::from(CORE_EXECUTABLE_NAME))
+}
+
+/// Places searched for the core executable, in order. The app's own directory comes first,
+/// then the directory of its resolved symlink, the bundle's Resources on macOS, a bin/
+/// subdirectory and finally the working directory.
+fn core_search_paths() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Ok(current_exe) = std::env::current_exe() {
+        let exes = [Some(current_exe.clone()), std::fs::canonicalize(&current_exe).ok()];
+        for exe in exes.into_iter().flatten() {
+            let Some(dir) = exe.parent() else { continue };
+            dirs.push(dir.to_path_buf());
+            // Contents/MacOS/<app> in an app bundle, resources live next to it
+            if cfg!(target_os = "macos") && dir.ends_with("Contents/MacOS") {
+                if let Some(contents) = dir.parent() {
+                    dirs.push(contents.join("Resources"));
+                }
+            }
+            dirs.push(dir.join("bin"));
+        }
+    }
+    if let Ok(cwd) = std::env::current_dir() {
+        dirs.push(cwd);
+    }
+
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        let path = dir.join(CORE_EXECUTABLE_NAME);
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+// PID of an externally launched core taken over with adopt_external_core.
+// A Child can't be reattached, so the process is managed by PID instead.
+static ADOPTED_CORE_PID: Lazy<Mutex<Option<u32>>> = Lazy::new(|| Mutex::new(None));
+
+/// Check whether a process with the given PID exists on Windows
+#[cfg(windows)]
+fn is_pid_alive(pid: u32) -> bool {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let filter = format!("PID eq {}", pid);
+    Command::new("tasklist")
+        .args(["/FI", &filter, "/FO", "CSV", "/NH"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains(&format!("\"{}\"", pid)))
+        .unwrap_or(false)
+}
+
+/// Check whether a process with the given PID exists on Unix
+#[cfg(unix)]
+fn is_pid_alive(pid: u32) -> bool {
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Forcibly terminate a process by PID on Windows
+#[cfg(windows)]
+fn kill_pid(pid: u32) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let output = Command::new("taskkill")
+        .args(["/F", "/PID", &pid.to_string()])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to run taskkill: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to kill process {}: {}",
+            pid,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Forcibly terminate a process by PID on Unix
+#[cfg(unix)]
+fn kill_pid(pid: u32) -> Result<(), String> {
+    let output = Command::new("kill")
+        .args(["-KILL", &pid.to_string()])
+        .output()
+        .map_err(|e| format!("Failed to run kill: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to kill process {}: {}",
+            pid,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// PID of the adopted core if it is still running, forgetting it once it has exited
+fn adopted_core_pid() -> Option<u32> {
+    let mut adopted = ADOPTED_CORE_PID.lock().unwrap();
+    let pid = (*adopted)?;
+    if is_pid_alive(pid) {
+        return Some(pid);
+    }
+    *adopted = None;
+    None
+}
+
+// Log files ConnectToolCore may write, relative to its executable directory
+const CORE_LOG_FILE_CANDIDATES: &[&str] = &["ConnectToolCore.log", "logs/ConnectToolCore.log"];
+
+/// Get the path to the log file written by ConnectToolCore
+fn get_core_log_file_path() -> PathBuf {
+    // User-specified core log file, for cores configured to log elsewhere
+    if let Some(path) = read_settings(|s| s.core_log_path_override.clone()) {
+        return path;
+    }
+
+    let core_path = get_core_executable_path();
+    let core_dir = core_path.parent().unwrap_or(Path::new("."));
+    CORE_LOG_FILE_CANDIDATES
+        .iter()
+        .map(|name| core_dir.join(name))
+        .find(|path| path.is_file())
+        .unwrap_or_else(|| core_dir.join(CORE_LOG_FILE_CANDIDATES[0]))
+}
+
+/// Find a ConnectToolCore process that was not started by this app on Windows
+#[cfg(windows)]
+fn find_external_core_pid() -> Option<u32> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let filter = format!("IMAGENAME eq {}", CORE_EXECUTABLE_NAME);
+    let output = Command::new("tasklist")
+        .args(["/FI", &filter, "/FO", "CSV", "/NH"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()?;
+
+    let output_str = String::from_utf8_lossy(&output.stdout);
+
+    // Format: "ConnectToolCore.exe","PID","Session Name","Session#","Mem Usage"
+    for line in output_str.lines() {
+        if line.contains(CORE_EXECUTABLE_NAME) {
+            let parts: Vec<&str> = line.split(',').collect();
+            if parts.len() >= 2 {
+                if let Ok(pid) = parts[1].trim_matches('"').parse::<u32>() {
+                    return Some(pid);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Find a ConnectToolCore process that was not started by this app on Unix
+#[cfg(unix)]
+fn find_external_core_pid() -> Option<u32> {
+    let output = Command::new("pgrep")
+        .args(["-x", CORE_EXECUTABLE_NAME])
+        .output()
+        .ok()?;
+
+    if output.status.success() {
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        if let Ok(pid) = output_str.trim().lines().next()?.parse::<u32>() {
+            return Some(pid);
+        }
+    }
+    None
+}
+
+// Number of core output lines kept for get_core_logs
+const CORE_LOG_CAPACITY: usize = 1000;
+
+// Most recent lines written by ConnectToolCore
+static CORE_LOGS: Lazy<Mutex<VecDeque<CoreLogLine>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(CORE_LOG_CAPACITY)));
+
+/// A single line of ConnectToolCore output, also the payload of the core-log event
+#[derive(Clone, serde::Serialize)]
+pub struct CoreLogLine {
+    pub level: String,
+    pub line: String,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+/// Record a line of core output and forward it to the frontend
+fn push_core_log(level: &str, line: String) {
+    let entry = CoreLogLine {
+        level: level.to_string(),
+        line,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+    };
+
+    {
+        let mut logs = CORE_LOGS.lock().unwrap();
+        if logs.len() == CORE_LOG_CAPACITY {
+            logs.pop_front();
+        }
+        logs.push_back(entry.clone());
+    }
+
+    if let Some(app) = APP_HANDLE.get() {
+        let _ = app.emit("core-log", entry);
+    }
+}
+
+/// Spawn threads that read the child's stdout/stderr line by line
+fn spawn_core_log_readers(child: &mut Child) {
+    use std::io::BufRead;
+
+    fn forward<R: std::io::Read + Send + 'static>(reader: R, level: &'static str) {
+        std::thread::spawn(move || {
+            for line in std::io::BufReader::new(reader).lines() {
+                match line {
+                    Ok(line) => push_core_log(level, line),
+                    Err(_) => break,
+                }
+            }
+        });
+    }
+
+    if let Some(stdout) = child.stdout.take() {
+        forward(stdout, "info");
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward(stderr, "error");
+    }
+}
+
+/// Check if the core process is running by checking the managed process
+fn check_core_process_running() -> (bool, Option<u32>) {
+    let mut guard = match CORE_PROCESS.try_lock() {
+        Ok(guard) => guard,
+        // A start or stop may be waiting on the OS with the lock held, answer from the last known PID
+        Err(std::sync::TryLockError::WouldBlock) => {
+            let pid = MANAGED_CORE_PID.load(Ordering::SeqCst);
+            return (pid != 0, Some(pid).filter(|&pid| pid != 0));
+        }
+        // The slot only holds the child handle, which a panic elsewhere leaves usable
+        Err(std::sync::TryLockError::Poisoned(e)) => {
+            CORE_PROCESS.clear_poison();
+            e.into_inner()
+        }
+    };
+    
+    if let Some(ref mut child) = *guard {
+        // Try to check if process is still running
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                // Process has exited, on its own unless stop_core asked it to
+                emit_core_exited(child.id(), Some(status), !CORE_SHOULD_RUN.load(Ordering::SeqCst));
+                *guard = None;
+                MANAGED_CORE_PID.store(0, Ordering::SeqCst);
+                *CORE_STARTED_AT.lock().unwrap() = None;
+                (false, None)
+            }
+            Ok(None) => {
+                // Process is still running
+                (true, Some(child.id()))
+            }
+            Err(_) => {
+                // Error checking, assume not running
+                emit_core_exited(child.id(), None, !CORE_SHOULD_RUN.load(Ordering::SeqCst));
+                *guard = None;
+                MANAGED_CORE_PID.store(0, Ordering::SeqCst);
+                *CORE_STARTED_AT.lock().unwrap() = None;
+                (false, None)
+            }
+        }
+    } else {
+        (false, None)
+    }
+}
+
+/// Run a CORE_PROCESS operation on the blocking pool. Killing and reaping the child waits on the
+/// OS, which must not stall the async runtime other commands are running on.
+async fn run_core_blocking<T: Send + 'static>(
+    op: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tokio::task::spawn_blocking(op)
+        .await
+        .map_err(|e| format!("Core process task failed: {}", e))?
+}
+
+/// Arguments and environment passed to ConnectToolCore at launch
+#[derive(Clone, Default, serde::Serialize)]
+pub struct CoreLaunchOptions {
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+}
+
+// Options used for the most recent launch, reused by restarts
+static CORE_LAUNCH_OPTIONS: Lazy<Mutex<CoreLaunchOptions>> =
+    Lazy::new(|| Mutex::new(CoreLaunchOptions::default()));
+
+/// Reject arguments that look like shell syntax; we never use a shell, but such input is almost
+/// certainly a mistake or an injection attempt
+fn validate_core_launch_options(options: &CoreLaunchOptions) -> Result<(), String> {
+    const FORBIDDEN: &[char] = &[';', '|', '&', '$', '`', '<', '>', '\n', '\r', '\0'];
+
+    for arg in &options.args {
+        if arg.contains(FORBIDDEN) {
+            return Err(format!("Invalid character in argument: {}", arg));
+        }
+    }
+    for (key, value) in &options.env {
+        if key.is_empty() || key.contains('=') || key.contains('\0') || value.contains('\0') {
+            return Err(format!("Invalid environment variable: {}", key));
+        }
+    }
+    Ok(())
+}
+
+/// Payload of the core-exited event
+#[derive(Clone, serde::Serialize)]
+pub struct CoreExitedEvent {
+    pub pid: u32,
+    /// None when the core was terminated by a signal or the status is unknown
+    pub code: Option<i32>,
+    /// Whether the exit came from stop_core or a restart rather than a crash
+    pub requested: bool,
+}
+
+/// Notify the frontend that the managed core has exited
+fn emit_core_exited(pid: u32, status: Option<std::process::ExitStatus>, requested: bool) {
+    let code = status.and_then(|status| status.code());
+    if requested {
+        tracing::info!(pid, ?code, "ConnectToolCore exited");
+    } else {
+        tracing::warn!(pid, ?code, "ConnectToolCore exited unexpectedly");
+    }
+    if let Some(app) = APP_HANDLE.get() {
+        let _ = app.emit("core-exited", CoreExitedEvent { pid, code, requested });
+    }
+}
+
+/// Poll the managed core until it exits or is replaced. The Child stays in CORE_PROCESS,
+/// so its exit is picked up with try_wait, which emits core-exited when it reaps the child.
+fn watch_core_exit(pid: u32) {
+    const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let (is_running, current_pid) = check_core_process_running();
+        if !is_running || current_pid != Some(pid) {
+            break;
+        }
+    }
+}
+
+/// Spawn ConnectToolCore into the given process slot, reusing a child that is still running.
+/// The caller must hold the CORE_PROCESS lock.
+fn spawn_core_locked(slot: &mut Option<Child>) -> Result<(bool, Option<u32>), String> {
+    let core_path = get_core_executable_path();
     
-    let state = if enabled { "True" } else { "False" };
-    let cmd = format!(
-        "Set-NetFirewallProfile -Profile Domain,Public,Private -Enabled {}",
-        state
-    );
+    if !core_path.exists() {
+        return Err(format!("ConnectToolCore not found at: {}", core_path.display()));
+    }
     
-    let output = Command::new("powershell")
-        .args(["-Command", &cmd])
-        .creation_flags(CREATE_NO_WINDOW)
-        .output()
-        .map_err(|e| format!("Failed to execute PowerShell: {}", e))?;
+    // Check if already running
+    if let Some(ref mut child) = *slot {
+        match child.try_wait() {
+            Ok(None) => {
+                // Already running
+                return Ok((true, Some(child.id())));
+            }
+            _ => {
+                // Process ended, clear it
+                *slot = None;
+                MANAGED_CORE_PID.store(0, Ordering::SeqCst);
+            }
+        }
+    }
+    
+    // The core can't bind its socket in a directory that doesn't exist
+    if let ConnectionMode::Uds { path } = connection_mode() {
+        if let Some(dir) = missing_socket_dir(&path) {
+            std::fs::create_dir_all(&dir)
+                .map_err(|e| format!("Failed to create socket directory {}: {}", dir.display(), e))?;
+        }
+    }
+    
+    let options = CORE_LAUNCH_OPTIONS.lock().unwrap().clone();
+    let mut command = Command::new(&core_path);
+    command
+        .current_dir(core_path.parent().unwrap_or(std::path::Path::new(".")))
+        .args(&options.args)
+        .envs(&options.env);
+    
+    // A visible console shows the core's output itself, otherwise it is captured for the in-app log view
+    let console_visible = cfg!(windows) && read_settings(|s| s.core_console_visible);
+    if !console_visible {
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    }
+    
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // 用户要求显示控制台时为核心打开独立的控制台窗口；
+        // 否则日志通过管道转发到前端，不再需要单独的控制台窗口
+        const CREATE_NEW_CONSOLE: u32 = 0x00000010;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        command.creation_flags(if console_visible { CREATE_NEW_CONSOLE } else { CREATE_NO_WINDOW });
+    }
+    
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to start ConnectToolCore: {}", e))?;
+    spawn_core_log_readers(&mut child);
+    
+    let pid = child.id();
+    tracing::info!(pid, path = %core_path.display(), "started ConnectToolCore");
+    std::thread::spawn(move || watch_core_exit(pid));
+    *slot = Some(child);
+    MANAGED_CORE_PID.store(pid, Ordering::SeqCst);
+    *CORE_STARTED_AT.lock().unwrap() = Some(CoreStartTime {
+        instant: std::time::Instant::now(),
+        time: chrono::Utc::now(),
+    });
+    CORE_SHOULD_RUN.store(true, Ordering::SeqCst);
+    
+    Ok((true, Some(pid)))
+}
+
+/// Wait until the core accepts connections on its socket
+async fn wait_for_core_socket() -> Result<(), String> {
+    const POLL_INTERVAL: Duration = Duration::from_millis(100);
+    const READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+    let path = socket_path();
+    let deadline = tokio::time::Instant::now() + READY_TIMEOUT;
+    loop {
+        if connect_uds(&path).await.is_ok() {
+            return Ok(());
+        }
+        // No point waiting out the timeout if the core already exited
+        if !check_core_process_running().0 {
+            return Err("ConnectToolCore exited before its socket became ready".to_string());
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return Err("core started but socket never became ready".to_string());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Kill the child in the given process slot and wait for it to exit.
+/// The caller must hold the CORE_PROCESS lock.
+fn kill_core_locked(slot: &mut Option<Child>) -> Result<(), String> {
+    if let Some(ref mut child) = *slot {
+        // Try to kill the process
+        child.kill().map_err(|e| format!("Failed to kill ConnectToolCore: {}", e))?;
+        
+        // Wait for it to finish
+        let status = child.wait().ok();
+        emit_core_exited(child.id(), status, true);
+        
+        *slot = None;
+        MANAGED_CORE_PID.store(0, Ordering::SeqCst);
+        *CORE_STARTED_AT.lock().unwrap() = None;
+    }
+    Ok(())
+}
+
+/// Start the ConnectToolCore process with the given launch options
+fn start_core_process(options: CoreLaunchOptions) -> Result<(bool, Option<u32>), String> {
+    let mut guard = CORE_PROCESS.lock().unwrap();
+    *CORE_LAUNCH_OPTIONS.lock().unwrap() = options;
+    spawn_core_locked(&mut guard)
+}
+
+/// Stop an adopted core by PID, gracefully first like stop_core_process
+async fn stop_adopted_core(graceful_timeout: Duration) -> Result<GracefulShutdownResult, String> {
+    let Some(pid) = adopted_core_pid() else {
+        return Ok(GracefulShutdownResult::NotRunning);
+    };
+
+    request_core_shutdown(pid).await;
+    invalidate_client();
+
+    let deadline = tokio::time::Instant::now() + graceful_timeout;
+    let mut result = GracefulShutdownResult::Forced;
+    while tokio::time::Instant::now() < deadline {
+        if !is_pid_alive(pid) {
+            result = GracefulShutdownResult::Graceful;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    if matches!(result, GracefulShutdownResult::Forced) {
+        kill_pid(pid)?;
+    }
+
+    *ADOPTED_CORE_PID.lock().unwrap() = None;
+    emit_core_exited(pid, None, true);
+    let _ = remove_stale_socket();
+    Ok(result)
+}
+
+/// Ask the managed core to exit on its own: the Shutdown RPC first, then SIGTERM on Unix
+async fn request_core_shutdown(pid: u32) {
+    let rpc = tokio::time::timeout(Duration::from_secs(2), async {
+        let mut client = get_client().await.ok()?;
+        let result = client.shutdown(ShutdownRequest {}).await.map_err(rpc_error);
+        record_core_feature("shutdown", result).ok()
+    })
+    .await;
+    if matches!(rpc, Ok(Some(_))) {
+        return;
+    }
+
+    #[cfg(unix)]
+    let _ = Command::new("kill").args(["-TERM", &pid.to_string()]).output();
+
+    #[cfg(not(unix))]
+    let _ = pid;
+}
+
+// How long the core gets to exit on its own before it is killed
+const CORE_GRACEFUL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Stop the ConnectToolCore process, giving it a chance to exit cleanly before killing it
+async fn stop_core_process() -> Result<GracefulShutdownResult, String> {
+    stop_core_process_within(CORE_GRACEFUL_TIMEOUT).await
+}
+
+/// Stop the core like stop_core_process, killing it once graceful_timeout has passed
+async fn stop_core_process_within(graceful_timeout: Duration) -> Result<GracefulShutdownResult, String> {
+    CORE_SHOULD_RUN.store(false, Ordering::SeqCst);
+
+    let (is_running, pid) = check_core_process_running();
+    let Some(pid) = pid.filter(|_| is_running) else {
+        return stop_adopted_core(graceful_timeout).await;
+    };
+
+    request_core_shutdown(pid).await;
+    // The channel is useless once the core is gone
+    invalidate_client();
+
+    let deadline = tokio::time::Instant::now() + graceful_timeout;
+    while tokio::time::Instant::now() < deadline {
+        // Also reaps the exited child from CORE_PROCESS
+        if !check_core_process_running().0 {
+            let _ = remove_stale_socket();
+            return Ok(GracefulShutdownResult::Graceful);
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    run_core_blocking(|| kill_core_locked(&mut CORE_PROCESS.lock().unwrap())).await?;
+    // A killed core never gets to unlink its socket
+    let _ = remove_stale_socket();
+    Ok(GracefulShutdownResult::Forced)
+}
+
+/// Kill the core, managed or adopted, without asking it to shut down first
+fn kill_core_now() -> Result<GracefulShutdownResult, String> {
+    CORE_SHOULD_RUN.store(false, Ordering::SeqCst);
+    invalidate_client();
+
+    let mut result = GracefulShutdownResult::NotRunning;
+    {
+        let mut slot = CORE_PROCESS.lock().unwrap();
+        if slot.is_some() {
+            kill_core_locked(&mut slot)?;
+            result = GracefulShutdownResult::Forced;
+        }
+    }
+    if let Some(pid) = adopted_core_pid() {
+        kill_pid(pid)?;
+        *ADOPTED_CORE_PID.lock().unwrap() = None;
+        emit_core_exited(pid, None, true);
+        result = GracefulShutdownResult::Forced;
+    }
+
+    let _ = remove_stale_socket();
+    Ok(result)
+}
+
+/// Remove the socket file if nothing is listening on it any more.
+/// Returns whether a file was removed.
+#[cfg(unix)]
+fn remove_stale_socket() -> Result<bool, String> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let path = socket_path();
+    let metadata = match std::fs::symlink_metadata(&path) {
+        Ok(metadata) => metadata,
+        Err(_) => return Ok(false),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(format!("{} is not a socket, refusing to remove it", path));
+    }
+    if check_core_process_running().0 {
+        return Err("ConnectToolCore is still running".to_string());
+    }
+
+    // Only a refused connection proves the socket is dead, anything else might be a live core
+    match std::os::unix::net::UnixStream::connect(&path) {
+        Ok(_) => Err(format!("A process is still listening on {}", path)),
+        Err(e) if e.kind() == std::io::ErrorKind::ConnectionRefused => {
+            std::fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove {}: {}", path, e))?;
+            Ok(true)
+        }
+        Err(e) => Err(format!("Failed to probe {}: {}", path, e)),
+    }
+}
+
+#[cfg(not(unix))]
+fn remove_stale_socket() -> Result<bool, String> {
+    Ok(false)
+}
+
+/// Stop and start the ConnectToolCore process under a single lock,
+/// returning whether a previous instance was running
+fn restart_core_process() -> Result<(bool, Option<u32>), String> {
+    let mut guard = CORE_PROCESS.lock().unwrap();
+    // A child that already exited is still in the slot until it is reaped
+    let was_running = guard.as_mut().is_some_and(|child| matches!(child.try_wait(), Ok(None)));
+    kill_core_locked(&mut guard)?;
+    let (_, pid) = spawn_core_locked(&mut guard)?;
+    Ok((was_running, pid))
+}
+
+/// Payload of the core-restarted event
+#[derive(Clone, serde::Serialize)]
+pub struct CoreRestartedEvent {
+    pub pid: Option<u32>,
+    pub attempt: u32,
+}
+
+/// Watch the managed core and restart it with exponential backoff after unexpected exits
+fn run_core_supervisor(app: tauri::AppHandle) {
+    const MAX_BACKOFF: Duration = Duration::from_secs(30);
+    // How long a restarted core must stay up before the backoff is reset
+    const STABLE_UPTIME: Duration = Duration::from_secs(30);
+
+    let mut backoff = Duration::from_secs(1);
+    let mut attempt = 0;
+    let mut last_restart: Option<std::time::Instant> = None;
+
+    loop {
+        std::thread::sleep(Duration::from_secs(1));
+
+        if !CORE_AUTORESTART.load(Ordering::SeqCst) || !CORE_SHOULD_RUN.load(Ordering::SeqCst) {
+            continue;
+        }
+
+        let (is_running, _) = check_core_process_running();
+        if is_running {
+            if last_restart.is_some_and(|t| t.elapsed() >= STABLE_UPTIME) {
+                backoff = Duration::from_secs(1);
+                attempt = 0;
+                last_restart = None;
+            }
+            continue;
+        }
+
+        std::thread::sleep(backoff);
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+
+        // The user may have stopped the core or disabled restarts while we waited
+        if !CORE_AUTORESTART.load(Ordering::SeqCst) || !CORE_SHOULD_RUN.load(Ordering::SeqCst) {
+            continue;
+        }
+
+        attempt += 1;
+        last_restart = Some(std::time::Instant::now());
+        // Relaunch with the options of the previous start
+        let result = spawn_core_locked(&mut CORE_PROCESS.lock().unwrap());
+        match result {
+            Ok((_, pid)) => {
+                tracing::warn!(attempt, ?pid, "restarted ConnectToolCore after unexpected exit");
+                let _ = app.emit("core-restarted", CoreRestartedEvent { pid, attempt });
+            }
+            Err(e) => tracing::error!(attempt, error = %e, "failed to restart ConnectToolCore"),
+        }
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(app))]
+async fn set_core_autorestart(app: tauri::AppHandle, enabled: bool) -> Result<(), CommandError> {
+    CORE_AUTORESTART.store(enabled, Ordering::SeqCst);
+
+    if enabled && !CORE_SUPERVISOR_STARTED.swap(true, Ordering::SeqCst) {
+        std::thread::spawn(move || run_core_supervisor(app));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_core_status() -> Result<CoreStatusResponse, CommandError> {
+    let (is_running, pid) = check_core_process_running();
+    
+    if is_running {
+        let started_at = *CORE_STARTED_AT.lock().unwrap();
+        return Ok(CoreStatusResponse {
+            is_running,
+            pid,
+            managed: true,
+            uptime_secs: started_at.map(|start| start.instant.elapsed().as_secs()),
+            started_at: started_at.map(|start| start.time.to_rfc3339()),
+            message: format!("ConnectToolCore is running (PID: {})", pid.unwrap_or(0)),
+        });
+    }
+    
+    if let Some(adopted_pid) = adopted_core_pid() {
+        return Ok(CoreStatusResponse {
+            is_running: true,
+            pid: Some(adopted_pid),
+            managed: true,
+            uptime_secs: None,
+            started_at: None,
+            message: format!("ConnectToolCore is running (adopted, PID: {})", adopted_pid),
+        });
+    }
+    
+    // The core may have been launched outside the app
+    if let Some(external_pid) = find_external_core_pid() {
+        return Ok(CoreStatusResponse {
+            is_running: true,
+            pid: Some(external_pid),
+            managed: false,
+            uptime_secs: None,
+            started_at: None,
+            message: format!("ConnectToolCore is running externally (PID: {})", external_pid),
+        });
+    }
+    
+    Ok(CoreStatusResponse {
+        is_running: false,
+        pid: None,
+        managed: false,
+        uptime_secs: None,
+        started_at: None,
+        message: "ConnectToolCore is not running".to_string(),
+    })
+}
+
+/// Response structure for get_core_path_info command
+#[derive(serde::Serialize)]
+pub struct CorePathInfo {
+    pub expected_path: String,
+    pub exists: bool,
+    pub is_file: bool,
+    /// Whether the path comes from set_core_path_override
+    pub is_override: bool,
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_core_path_info() -> Result<CorePathInfo, CommandError> {
+    let core_path = get_core_executable_path();
+    Ok(CorePathInfo {
+        expected_path: core_path.to_string_lossy().to_string(),
+        exists: core_path.exists(),
+        is_file: core_path.is_file(),
+        is_override: core_path_override().is_some(),
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(app))]
+async fn open_core_folder(app: tauri::AppHandle) -> Result<(), CommandError> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let core_path = get_core_executable_path();
+    let core_dir = core_path
+        .parent()
+        .filter(|dir| dir.is_dir())
+        .ok_or_else(|| format!("Core directory not found for {}", core_path.display()))?;
+    app.opener()
+        .open_path(core_dir.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("Failed to open {}: {}", core_dir.display(), e))?;
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn set_core_path_override(path: String) -> Result<CorePathInfo, CommandError> {
+    let core_path = PathBuf::from(path.trim());
+    if !core_path.is_file() {
+        return Err(format!("No ConnectToolCore executable found at {}", core_path.display()).into());
+    }
+
+    modify_settings(|s| s.core_path_override = Some(core_path))?;
+
+    get_core_path_info().await
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn clear_core_path_override() -> Result<CorePathInfo, CommandError> {
+    modify_settings(|s| s.core_path_override = None)?;
+
+    // Report the default location now in effect
+    get_core_path_info().await
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_core_logs() -> Result<Vec<CoreLogLine>, CommandError> {
+    Ok(CORE_LOGS.lock().unwrap().iter().cloned().collect())
+}
+
+// Upper bound for get_core_log_tail, in lines and in bytes read from the end of the file
+const CORE_LOG_TAIL_MAX_LINES: usize = 2000;
+const CORE_LOG_TAIL_MAX_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Read the last lines of a file, reading backwards so large logs are not loaded whole
+fn read_file_tail(path: &Path, lines: usize) -> Result<Vec<String>, String> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let len = file
+        .metadata()
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+        .len();
+    let limit = len.saturating_sub(CORE_LOG_TAIL_MAX_BYTES);
+
+    const CHUNK_SIZE: u64 = 8192;
+    let mut start = len;
+    let mut buf: Vec<u8> = Vec::new();
+    // A trailing newline ends the last line rather than starting a new one
+    while start > limit && buf.iter().filter(|&&b| b == b'\n').count() <= lines {
+        let read_from = start.saturating_sub(CHUNK_SIZE).max(limit);
+        let mut chunk = vec![0; (start - read_from) as usize];
+        file.seek(SeekFrom::Start(read_from))
+            .and_then(|_| file.read_exact(&mut chunk))
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        chunk.extend_from_slice(&buf);
+        buf = chunk;
+        start = read_from;
+    }
+
+    let text = String::from_utf8_lossy(&buf);
+    let mut tail: Vec<String> = text.lines().map(str::to_string).collect();
+    // The first line may be cut off when reading stopped mid-file
+    if start > 0 && !tail.is_empty() {
+        tail.remove(0);
+    }
+    let skip = tail.len().saturating_sub(lines);
+    Ok(tail.split_off(skip))
+}
+
+/// Response structure for get_core_log_tail command
+#[derive(serde::Serialize)]
+pub struct CoreLogTailResponse {
+    pub path: String,
+    /// False when the core has not written a log file, lines is then empty
+    pub exists: bool,
+    pub lines: Vec<String>,
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_core_log_tail(lines: usize) -> Result<CoreLogTailResponse, CommandError> {
+    let path = get_core_log_file_path();
+    if !path.is_file() {
+        return Ok(CoreLogTailResponse {
+            path: path.to_string_lossy().to_string(),
+            exists: false,
+            lines: Vec::new(),
+        });
+    }
+
+    let lines = read_file_tail(&path, lines.min(CORE_LOG_TAIL_MAX_LINES))?;
+    Ok(CoreLogTailResponse {
+        path: path.to_string_lossy().to_string(),
+        exists: true,
+        lines,
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn set_core_log_path_override(path: String) -> Result<(), CommandError> {
+    let log_path = PathBuf::from(path.trim());
+    // The file itself may not exist until the core first logs
+    if log_path.is_dir() || !log_path.parent().is_some_and(|dir| dir.is_dir()) {
+        return Err(format!("Invalid core log path: {}", log_path.display()).into());
+    }
+
+    modify_settings(|s| s.core_log_path_override = Some(log_path))?;
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn clear_core_log_path_override() -> Result<(), CommandError> {
+    modify_settings(|s| s.core_log_path_override = None)?;
+    Ok(())
+}
+
+/// Show ConnectToolCore in its own console window on Windows, taking effect on the next start.
+/// While the console is visible the in-app log view receives no output.
+#[tauri::command]
+#[tracing::instrument]
+async fn set_core_console_visible(visible: bool) -> Result<bool, CommandError> {
+    modify_settings(|s| s.core_console_visible = visible)?;
+    Ok(visible)
+}
+
+/// Response structure for get_core_config command
+#[derive(serde::Serialize)]
+pub struct CoreConfigResponse {
+    /// Always false for now, the core has no RPC to report its own configuration
+    pub reported_by_core: bool,
+    pub core_path: String,
+    pub connection_mode: ConnectionMode,
+    /// Arguments of the most recent launch by this app
+    pub args: Vec<String>,
+    /// Names of the environment variables set at launch, values may hold secrets
+    pub env_keys: Vec<String>,
+    /// Whether the running core was started by this app, only then do args apply
+    pub managed: bool,
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_core_config() -> Result<CoreConfigResponse, CommandError> {
+    let options = CORE_LAUNCH_OPTIONS.lock().unwrap().clone();
+    let mut env_keys: Vec<String> = options.env.into_keys().collect();
+    env_keys.sort();
+
+    Ok(CoreConfigResponse {
+        reported_by_core: false,
+        core_path: get_core_executable_path().to_string_lossy().to_string(),
+        connection_mode: connection_mode(),
+        args: options.args,
+        env_keys,
+        managed: check_core_process_running().0,
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_core_version() -> Result<GetVersionResponse, CommandError> {
+    if mock_mode() {
+        return Ok(mock::version());
+    }
+    with_retry(|mut client| async move { client.get_version(GetVersionRequest {}).await }).await
+}
+
+/// get_core_version for use right after start_core, when the socket may bind a beat late.
+/// Retries the first RPC at a fixed interval instead of with_retry's backoff.
+#[tauri::command]
+#[tracing::instrument]
+async fn get_core_version_after_start() -> Result<GetVersionResponse, CommandError> {
+    const ATTEMPTS: u32 = 10;
+    const RETRY_INTERVAL: Duration = Duration::from_millis(200);
+
+    if mock_mode() {
+        return Ok(mock::version());
+    }
+
+    let mut attempt = 1;
+    loop {
+        let error = match try_get_client().await {
+            Ok(mut client) => match client.get_version(GetVersionRequest {}).await {
+                Ok(response) => return Ok(response.into_inner()),
+                Err(status) => rpc_error(status),
+            },
+            Err(e) => connect_error(&e),
+        };
+        if attempt >= ATTEMPTS {
+            return Err(error);
+        }
+        tracing::debug!(attempt, error = %error, "core not ready yet, retrying get_version");
+        // A failed channel would otherwise be reused by the next attempt
+        invalidate_client();
+        tokio::time::sleep(RETRY_INTERVAL).await;
+        attempt += 1;
+    }
+}
+
+// Core versions this build of the GUI can talk to
+const SUPPORTED_CORE_VERSIONS: &str = ">=0.1.0, <0.2.0";
+
+/// Response structure for get_versions command
+#[derive(serde::Serialize)]
+pub struct VersionsResponse {
+    pub gui_version: String,
+    /// None if ConnectToolCore is unreachable
+    pub core_version: Option<String>,
+    /// False when the core is unreachable or reports an unparseable version
+    pub compatible: bool,
+}
+
+/// Check a core version string against SUPPORTED_CORE_VERSIONS
+fn is_core_version_compatible(core_version: &str) -> bool {
+    let req = semver::VersionReq::parse(SUPPORTED_CORE_VERSIONS).expect("valid version requirement");
+    semver::Version::parse(core_version.trim().trim_start_matches('v'))
+        .map(|version| req.matches(&version))
+        .unwrap_or(false)
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_versions() -> Result<VersionsResponse, CommandError> {
+    let core_version = get_core_version().await.ok().map(|response| response.version);
+    Ok(VersionsResponse {
+        gui_version: env!("CARGO_PKG_VERSION").to_string(),
+        compatible: core_version.as_deref().is_some_and(is_core_version_compatible),
+        core_version,
+    })
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Failed to set firewall status: {}", stderr));
+// Features reported by get_core_capabilities. The core has no capabilities RPC and the
+// version that introduced each RPC isn't recorded, so support is learned from the calls:
+// a core that answers Unimplemented lacks the feature.
+const CORE_FEATURES: &[&str] = &[
+    "lobby",
+    "friend_lobbies",
+    "invite_friend",
+    "kick_member",
+    "vpn_status",
+    "vpn_routing_table",
+    "vpn_status_stream",
+    "vpn_regions",
+    "set_vpn_region",
+    "shutdown",
+];
+
+// Whether the connected core implements each feature, cleared whenever a new channel connects
+static CORE_FEATURE_SUPPORT: Lazy<Mutex<HashMap<&'static str, bool>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Remember whether the core implements a feature from the outcome of one of its RPCs
+fn record_core_feature<T>(feature: &'static str, result: Result<T, CommandError>) -> Result<T, CommandError> {
+    let supported = match &result {
+        Ok(_) => Some(true),
+        Err(CommandError::Rpc { code, .. }) => Some(*code != tonic::Code::Unimplemented as i32),
+        // Transport failures and cancellations say nothing about the core
+        Err(_) => None,
+    };
+    if let Some(supported) = supported {
+        CORE_FEATURE_SUPPORT.lock().unwrap().insert(feature, supported);
     }
-    
-    let action = if enabled { "enabled" } else { "disabled" };
-    Ok(FirewallToggleResponse {
-        success: true,
-        message: format!("Windows Firewall {} successfully", action),
-    })
+    result
 }
 
-#[cfg(not(windows))]
-fn get_firewall_status_windows() -> Result<FirewallStatusResponse, String> {
-    Err("Firewall management is only supported on Windows".to_string())
+/// Response structure for get_core_capabilities command
+#[derive(serde::Serialize)]
+pub struct CoreCapabilitiesResponse {
+    pub core_version: String,
+    pub compatible: bool,
+    /// Features the core has answered
+    pub capabilities: Vec<String>,
+    /// Features the core answered with Unimplemented, features in neither list haven't been used yet
+    pub unsupported: Vec<String>,
 }
 
-#[cfg(not(windows))]
-fn set_firewall_status_windows(_enabled: bool) -> Result<FirewallToggleResponse, String> {
-    Err("Firewall management is only supported on Windows".to_string())
+#[tauri::command]
+#[tracing::instrument]
+async fn get_core_capabilities() -> Result<CoreCapabilitiesResponse, CommandError> {
+    let core_version = get_core_version().await?.version;
+    let (capabilities, unsupported) = if mock_mode() {
+        // The mock answers every RPC
+        (CORE_FEATURES.iter().map(|feature| feature.to_string()).collect(), Vec::new())
+    } else {
+        let support = CORE_FEATURE_SUPPORT.lock().unwrap();
+        let with_support = |supported: bool| {
+            CORE_FEATURES
+                .iter()
+                .filter(|feature| support.get(*feature) == Some(&supported))
+                .map(|feature| feature.to_string())
+                .collect::<Vec<_>>()
+        };
+        (with_support(true), with_support(false))
+    };
+    Ok(CoreCapabilitiesResponse {
+        compatible: is_core_version_compatible(&core_version),
+        capabilities,
+        unsupported,
+        core_version,
+    })
+}
+
+/// Check that ConnectToolCore answers RPCs within the given timeout
+async fn ping_core_with_timeout(timeout: Duration) -> PingCoreResponse {
+    if mock_mode() {
+        return PingCoreResponse {
+            reachable: true,
+            latency_ms: Some(0),
+            message: format!("ConnectToolCore {} is reachable", mock::VERSION),
+        };
+    }
+    let started = std::time::Instant::now();
+    let result = tokio::time::timeout(timeout, async {
+        let mut client = get_client().await?;
+        client
+            .get_version(GetVersionRequest {})
+            .await
+            .map_err(rpc_error)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(response)) => PingCoreResponse {
+            reachable: true,
+            latency_ms: Some(started.elapsed().as_millis() as u64),
+            message: format!("ConnectToolCore {} is reachable", response.into_inner().version),
+        },
+        Ok(Err(e)) => PingCoreResponse {
+            reachable: false,
+            latency_ms: None,
+            message: e.to_string(),
+        },
+        Err(_) => PingCoreResponse {
+            reachable: false,
+            latency_ms: None,
+            message: CORE_UNREACHABLE_TIMEOUT.to_string(),
+        },
+    }
 }
 
 #[tauri::command]
-async fn get_firewall_status() -> Result<FirewallStatusResponse, String> {
-    get_firewall_status_windows()
+#[tracing::instrument]
+async fn ping_core() -> Result<PingCoreResponse, CommandError> {
+    Ok(ping_core_with_timeout(Duration::from_secs(2)).await)
+}
+
+// Upper bound for benchmark_socket iterations
+const MAX_BENCHMARK_ITERATIONS: u32 = 1000;
+
+/// Response structure for benchmark_socket command, latencies in milliseconds
+#[derive(serde::Serialize)]
+pub struct SocketBenchmarkResponse {
+    pub iterations: u32,
+    pub failures: u32,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub avg_ms: f64,
+    pub p95_ms: f64,
+    pub calls_per_second: f64,
 }
 
+/// Time sequential GetVersion calls over the shared channel, to tell transport
+/// or core slowness apart from the GUI
 #[tauri::command]
-async fn set_firewall(enabled: bool) -> Result<FirewallToggleResponse, String> {
-    set_firewall_status_windows(enabled)
+#[tracing::instrument]
+async fn benchmark_socket(iterations: u32) -> Result<SocketBenchmarkResponse, CommandError> {
+    if mock_mode() {
+        return Err("Socket benchmark is unavailable in mock mode".into());
+    }
+    if iterations == 0 || iterations > MAX_BENCHMARK_ITERATIONS {
+        return Err(format!("iterations must be between 1 and {}", MAX_BENCHMARK_ITERATIONS).into());
+    }
+
+    let mut client = get_client().await?;
+    let mut latencies = Vec::with_capacity(iterations as usize);
+    let mut last_error = None;
+    let started = std::time::Instant::now();
+    for _ in 0..iterations {
+        let call_started = std::time::Instant::now();
+        match client.get_version(GetVersionRequest {}).await {
+            Ok(_) => latencies.push(call_started.elapsed().as_secs_f64() * 1000.0),
+            Err(status) => last_error = Some(rpc_error(status)),
+        }
+    }
+    let elapsed = started.elapsed().as_secs_f64();
+
+    if latencies.is_empty() {
+        return Err(last_error.unwrap_or_else(CommandError::not_connected));
+    }
+    latencies.sort_by(|a, b| a.total_cmp(b));
+    let p95_index = ((latencies.len() as f64 * 0.95).ceil() as usize).clamp(1, latencies.len()) - 1;
+
+    Ok(SocketBenchmarkResponse {
+        iterations,
+        failures: iterations - latencies.len() as u32,
+        min_ms: latencies[0],
+        max_ms: latencies[latencies.len() - 1],
+        avg_ms: latencies.iter().sum::<f64>() / latencies.len() as f64,
+        p95_ms: latencies[p95_index],
+        calls_per_second: if elapsed > 0.0 { latencies.len() as f64 / elapsed } else { 0.0 },
+    })
 }
 
-// ============== End Firewall Management ==============
+// Number of ping samples kept for get_core_health_history
+const CORE_HEALTH_CAPACITY: usize = 100;
 
-// ============== ConnectToolCore Management ==============
+/// One ping_core result recorded by the health monitor
+#[derive(Clone, serde::Serialize)]
+pub struct CoreHealthSample {
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub latency_ms: Option<u64>,
+    pub ok: bool,
+}
 
-/// Get the path to ConnectToolCore executable
-fn get_core_executable_path() -> PathBuf {
-    let current_exe = std::env::current_exe().unwrap_or_default();
-    let current_dir = current_exe.parent().unwrap_or(std::path::Path::new("."));
-    
-    #[cfg(windows)]
-    let core_name = "ConnectToolCore.exe";
-    #[cfg(not(windows))]
-    let core_name = "ConnectToolCore";
-    
-    current_dir.join(core_name)
+static CORE_HEALTH_HISTORY: Lazy<Mutex<VecDeque<CoreHealthSample>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(CORE_HEALTH_CAPACITY)));
+
+// Background task pinging the core for the health history
+static CORE_HEALTH_TASK: Lazy<Mutex<Option<tauri::async_runtime::JoinHandle<()>>>> =
+    Lazy::new(|| Mutex::new(None));
+
+/// Abort the core health prober, returning whether one was running
+fn stop_core_health_task() -> bool {
+    let task = CORE_HEALTH_TASK.lock().unwrap().take();
+    if let Some(ref task) = task {
+        task.abort();
+    }
+    task.is_some()
 }
 
-/// Check if the core process is running by checking the managed process
-fn check_core_process_running() -> (bool, Option<u32>) {
-    let mut guard = CORE_PROCESS.lock().unwrap();
-    
-    if let Some(ref mut child) = *guard {
-        // Try to check if process is still running
-        match child.try_wait() {
-            Ok(Some(_status)) => {
-                // Process has exited
-                *guard = None;
-                (false, None)
-            }
-            Ok(None) => {
-                // Process is still running
-                (true, Some(child.id()))
-            }
-            Err(_) => {
-                // Error checking, assume not running
-                *guard = None;
-                (false, None)
+#[tauri::command]
+#[tracing::instrument]
+async fn start_core_health_monitor(interval_ms: u64) -> Result<(), CommandError> {
+    const MIN_INTERVAL_MS: u64 = 250;
+    if interval_ms < MIN_INTERVAL_MS {
+        return Err(format!("Health monitor interval must be at least {} ms", MIN_INTERVAL_MS).into());
+    }
+    let interval = Duration::from_millis(interval_ms);
+    // A probe must not outlive its slot, or samples would pile up behind a hung core
+    let probe_timeout = interval.min(Duration::from_secs(2));
+
+    let task = tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let ping = ping_core_with_timeout(probe_timeout).await;
+            let sample = CoreHealthSample {
+                timestamp: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_millis() as u64)
+                    .unwrap_or(0),
+                latency_ms: ping.latency_ms,
+                ok: ping.reachable,
+            };
+
+            let mut history = CORE_HEALTH_HISTORY.lock().unwrap();
+            if history.len() == CORE_HEALTH_CAPACITY {
+                history.pop_front();
             }
+            history.push_back(sample);
         }
-    } else {
-        (false, None)
+    });
+
+    // Replace any previous monitor
+    if let Some(previous) = CORE_HEALTH_TASK.lock().unwrap().replace(task) {
+        previous.abort();
     }
+    Ok(())
 }
 
-/// Start the ConnectToolCore process
-#[cfg(windows)]
-fn start_core_process() -> Result<(bool, Option<u32>), String> {
-    use std::os::windows::process::CommandExt;
-    // 使用 CREATE_NEW_CONSOLE 让 Core 在独立的控制台窗口中运行，方便用户查看日志
-    const CREATE_NEW_CONSOLE: u32 = 0x00000010;
-    
+#[tauri::command]
+#[tracing::instrument]
+async fn stop_core_health_monitor() -> Result<(), CommandError> {
+    stop_core_health_task();
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_core_health_history() -> Result<Vec<CoreHealthSample>, CommandError> {
+    Ok(CORE_HEALTH_HISTORY.lock().unwrap().iter().cloned().collect())
+}
+
+/// Marks a core start, stop or restart as in progress until dropped
+struct CoreTransitionGuard;
+
+impl CoreTransitionGuard {
+    /// Reject overlapping operations, e.g. from a double-clicked button
+    fn acquire() -> Result<Self, CommandError> {
+        if CORE_TRANSITION
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err("core operation already in progress".into());
+        }
+        Ok(CoreTransitionGuard)
+    }
+}
+
+impl Drop for CoreTransitionGuard {
+    fn drop(&mut self) {
+        CORE_TRANSITION.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Fail with CoreNotFound when the core executable is missing
+fn ensure_core_exists() -> Result<(), CommandError> {
     let core_path = get_core_executable_path();
-    
     if !core_path.exists() {
-        return Err(format!("ConnectToolCore not found at: {}", core_path.display()));
+        return Err(CommandError::CoreNotFound {
+            searched_path: core_path.display().to_string(),
+        });
     }
-    
-    let mut guard = CORE_PROCESS.lock().unwrap();
-    
-    // Check if already running
-    if let Some(ref mut child) = *guard {
-        match child.try_wait() {
-            Ok(None) => {
-                // Already running
-                return Ok((true, Some(child.id())));
-            }
-            _ => {
-                // Process ended, clear it
-                *guard = None;
-            }
+    Ok(())
+}
+
+/// Start the core unless one is already reachable
+async fn start_core_with_options(options: CoreLaunchOptions) -> Result<CoreControlResponse, CommandError> {
+    ensure_writable()?;
+    let _transition = CoreTransitionGuard::acquire()?;
+
+    // Don't spawn a duplicate if another instance already owns the socket
+    let (is_managed_running, _) = check_core_process_running();
+    if !is_managed_running && ping_core_with_timeout(Duration::from_secs(1)).await.reachable {
+        return Ok(CoreControlResponse {
+            success: true,
+            is_running: true,
+            pid: find_external_core_pid(),
+            message: "already running (external)".to_string(),
+        });
+    }
+
+    ensure_core_exists()?;
+    let (is_running, pid) = match run_core_blocking(move || start_core_process(options)).await {
+        Ok(result) => result,
+        Err(e) => {
+            return Ok(CoreControlResponse {
+                success: false,
+                is_running: false,
+                pid: None,
+                message: e,
+            });
+        }
+    };
+
+    // Only report success once the core can actually serve RPCs
+    if let Err(e) = wait_for_core_socket().await {
+        let (is_running, pid) = check_core_process_running();
+        return Ok(CoreControlResponse {
+            success: false,
+            is_running,
+            pid,
+            message: e,
+        });
+    }
+
+    Ok(CoreControlResponse {
+        success: true,
+        is_running,
+        pid,
+        message: "ConnectToolCore started successfully".to_string(),
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn adopt_external_core() -> Result<CoreControlResponse, CommandError> {
+    ensure_writable()?;
+    let _transition = CoreTransitionGuard::acquire()?;
+    if check_core_process_running().0 {
+        return Err("ConnectToolCore is already managed by this app".into());
+    }
+
+    let pid = find_external_core_pid().ok_or("No externally launched ConnectToolCore found")?;
+    *ADOPTED_CORE_PID.lock().unwrap() = Some(pid);
+    tracing::info!(pid, "adopted external ConnectToolCore");
+
+    Ok(CoreControlResponse {
+        success: true,
+        is_running: true,
+        pid: Some(pid),
+        message: format!("Adopted ConnectToolCore (PID: {})", pid),
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn start_core() -> Result<CoreControlResponse, CommandError> {
+    start_core_with_options(active_profile_launch_options()).await
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(env))]
+async fn start_core_with_args(
+    args: Vec<String>,
+    env: HashMap<String, String>,
+) -> Result<CoreControlResponse, CommandError> {
+    let options = CoreLaunchOptions { args, env };
+    validate_core_launch_options(&options)?;
+    start_core_with_options(options).await
+}
+
+/// Response structure for cleanup_socket command
+#[derive(serde::Serialize)]
+pub struct CleanupSocketResponse {
+    pub removed: bool,
+    pub message: String,
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn cleanup_socket() -> Result<CleanupSocketResponse, CommandError> {
+    ensure_writable()?;
+    let removed = remove_stale_socket()?;
+    Ok(CleanupSocketResponse {
+        removed,
+        message: if removed {
+            format!("Removed stale socket {}", socket_path())
+        } else {
+            "No stale socket to remove".to_string()
+        },
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn stop_core() -> Result<CoreControlResponse, CommandError> {
+    ensure_writable()?;
+    let _transition = CoreTransitionGuard::acquire()?;
+    match stop_core_process().await {
+        Ok(result) => Ok(CoreControlResponse {
+            success: true,
+            is_running: false,
+            pid: None,
+            message: match result {
+                GracefulShutdownResult::NotRunning => "ConnectToolCore is not running".to_string(),
+                GracefulShutdownResult::Graceful => "ConnectToolCore stopped gracefully".to_string(),
+                GracefulShutdownResult::Forced => {
+                    "ConnectToolCore did not exit in time and was killed".to_string()
+                }
+            },
+        }),
+        Err(e) => Ok(CoreControlResponse {
+            success: false,
+            is_running: true,
+            pid: None,
+            message: e,
+        }),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn restart_core() -> Result<CoreControlResponse, CommandError> {
+    ensure_writable()?;
+    let _transition = CoreTransitionGuard::acquire()?;
+    ensure_core_exists()?;
+    // An adopted core is replaced by one we spawn ourselves
+    let adopted_stopped = matches!(
+        stop_adopted_core(CORE_GRACEFUL_TIMEOUT).await?,
+        GracefulShutdownResult::Graceful | GracefulShutdownResult::Forced
+    );
+    let result = match run_core_blocking(restart_core_process).await {
+        Ok((was_running, pid)) => wait_for_core_socket().await.map(|()| (was_running, pid)),
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok((was_running, pid)) => Ok(CoreControlResponse {
+            success: true,
+            is_running: true,
+            pid,
+            message: if was_running || adopted_stopped {
+                "ConnectToolCore restarted successfully".to_string()
+            } else {
+                "ConnectToolCore started successfully".to_string()
+            },
+        }),
+        Err(e) => {
+            let (is_running, pid) = check_core_process_running();
+            Ok(CoreControlResponse {
+                success: false,
+                is_running,
+                pid,
+                message: e,
+            })
         }
     }
-    
-    // Start the process with a visible console window for log viewing
-    let child = Command::new(&core_path)
-        .current_dir(core_path.parent().unwrap_or(std::path::Path::new(".")))
-        .creation_flags(CREATE_NEW_CONSOLE)
-        .spawn()
-        .map_err(|e| format!("Failed to start ConnectToolCore: {}", e))?;
-    
-    let pid = child.id();
-    *guard = Some(child);
-    
-    // Wait a bit for the process to initialize
-    std::thread::sleep(std::time::Duration::from_millis(500));
-    
-    Ok((true, Some(pid)))
 }
 
-#[cfg(not(windows))]
-fn start_core_process() -> Result<(bool, Option<u32>), String> {
-    let core_path = get_core_executable_path();
-    
-    if !core_path.exists() {
-        return Err(format!("ConnectToolCore not found at: {}", core_path.display()));
+// ============== End ConnectToolCore Management ==============
+
+// ============== Core Profiles ==============
+
+/// A named core configuration: where it listens, which executable and which arguments
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct CoreProfile {
+    pub name: String,
+    pub socket_path: String,
+    /// Core executable, the default location when None
+    pub core_path: Option<String>,
+    pub args: Vec<String>,
+}
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+struct ProfilesState {
+    profiles: Vec<CoreProfile>,
+    active: Option<String>,
+}
+
+/// Launch options from the active profile, used when starting the core without explicit arguments
+fn active_profile_launch_options() -> CoreLaunchOptions {
+    let args = read_settings(|s| {
+        let state = &s.profiles;
+        state
+            .active
+            .as_ref()
+            .and_then(|name| state.profiles.iter().find(|profile| &profile.name == name))
+            .map(|profile| profile.args.clone())
+            .unwrap_or_default()
+    });
+    CoreLaunchOptions {
+        args,
+        env: HashMap::new(),
+    }
+}
+
+/// Response structure for profile commands
+#[derive(serde::Serialize)]
+pub struct ProfilesResponse {
+    pub profiles: Vec<CoreProfile>,
+    pub active: Option<String>,
+}
+
+fn profiles_response(state: &ProfilesState) -> ProfilesResponse {
+    ProfilesResponse {
+        profiles: state.profiles.clone(),
+        active: state.active.clone(),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn list_profiles() -> Result<ProfilesResponse, CommandError> {
+    Ok(read_settings(|s| profiles_response(&s.profiles)))
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn add_profile(
+    name: String,
+    socket_path: String,
+    core_path: Option<String>,
+    args: Vec<String>,
+) -> Result<ProfilesResponse, CommandError> {
+    ensure_writable()?;
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Profile name cannot be empty".into());
+    }
+    let socket_path = validate_socket_path(&socket_path)?;
+    let core_path = core_path
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty());
+    if let Some(ref path) = core_path {
+        if !Path::new(path).is_file() {
+            return Err(format!("No ConnectToolCore executable found at {}", path).into());
+        }
+    }
+    validate_core_launch_options(&CoreLaunchOptions {
+        args: args.clone(),
+        env: HashMap::new(),
+    })?;
+
+    Ok(try_modify_settings(|s| {
+        let state = &mut s.profiles;
+        if state.profiles.iter().any(|profile| profile.name == name) {
+            return Err(format!("Profile already exists: {}", name));
+        }
+        state.profiles.push(CoreProfile {
+            name,
+            socket_path,
+            core_path,
+            args,
+        });
+        Ok(profiles_response(state))
+    })?)
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn select_profile(name: String) -> Result<ProfilesResponse, CommandError> {
+    ensure_writable()?;
+    let profile = read_settings(|s| {
+        s.profiles
+            .profiles
+            .iter()
+            .find(|profile| profile.name == name)
+            .cloned()
+    })
+    .ok_or_else(|| format!("Profile not found: {}", name))?;
+    // Abort connects still in flight on the old profile's socket
+    reset_client();
+
+    {
+        // The managed core belongs to the old profile
+        let _transition = CoreTransitionGuard::acquire()?;
+        stop_core_process().await?;
+    }
+
+    set_connection_mode(ConnectionMode::Uds {
+        path: profile.socket_path.clone(),
+    })
+    .await?;
+    match profile.core_path {
+        Some(path) => set_core_path_override(path).await?,
+        None => clear_core_path_override().await?,
+    };
+
+    Ok(try_modify_settings(|s| {
+        s.profiles.active = Some(profile.name);
+        Ok(profiles_response(&s.profiles))
+    })?)
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn delete_profile(name: String) -> Result<ProfilesResponse, CommandError> {
+    ensure_writable()?;
+    Ok(try_modify_settings(|s| {
+        let state = &mut s.profiles;
+        let before = state.profiles.len();
+        state.profiles.retain(|profile| profile.name != name);
+        if state.profiles.len() == before {
+            return Err(format!("Profile not found: {}", name));
+        }
+        // The deleted profile's settings stay in effect, they are just no longer named
+        if state.active.as_deref() == Some(name.as_str()) {
+            state.active = None;
+        }
+        Ok(profiles_response(state))
+    })?)
+}
+
+// ============== End Core Profiles ==============
+
+// ============== System Status ==============
+
+/// Result of checking one subsystem, failures are reported per subsystem
+#[derive(serde::Serialize)]
+pub struct SubsystemStatus<T> {
+    pub ok: bool,
+    pub data: Option<T>,
+    pub error: Option<CommandError>,
+}
+
+/// Unwrap the result of a spawned subsystem check
+fn subsystem_status<T, E: std::fmt::Display>(
+    joined: Result<Result<T, CommandError>, E>,
+) -> SubsystemStatus<T> {
+    match joined {
+        Ok(Ok(data)) => SubsystemStatus {
+            ok: true,
+            data: Some(data),
+            error: None,
+        },
+        Ok(Err(e)) => SubsystemStatus {
+            ok: false,
+            data: None,
+            error: Some(e),
+        },
+        Err(e) => SubsystemStatus {
+            ok: false,
+            data: None,
+            error: Some(CommandError::Internal(format!("Status check failed: {}", e))),
+        },
+    }
+}
+
+/// Response structure for get_system_status command
+#[derive(serde::Serialize)]
+pub struct SystemStatusResponse {
+    pub steam: SubsystemStatus<FindSteamPathResponse>,
+    pub steam_running: SubsystemStatus<GetSteamStatusResponse>,
+    pub core: SubsystemStatus<PingCoreResponse>,
+    pub firewall: SubsystemStatus<FirewallStatusResponse>,
+}
+
+#[tauri::command]
+#[tracing::instrument]
+async fn get_system_status() -> Result<SystemStatusResponse, CommandError> {
+    // Spawned so the checks that shell out to system tools run in parallel
+    let (steam, steam_running, core, firewall) = tokio::join!(
+        tauri::async_runtime::spawn(find_steam()),
+        tauri::async_runtime::spawn(get_steam_running_status()),
+        tauri::async_runtime::spawn(ping_core()),
+        tauri::async_runtime::spawn(get_firewall_status()),
+    );
+
+    Ok(SystemStatusResponse {
+        steam: subsystem_status(steam),
+        steam_running: subsystem_status(steam_running),
+        core: subsystem_status(core),
+        firewall: subsystem_status(firewall),
+    })
+}
+
+/// Response structure for run_connection_diagnostics command
+#[derive(serde::Serialize)]
+pub struct ConnectionDiagnostics {
+    pub app_version: String,
+    pub connection_mode: ConnectionMode,
+    pub socket_path: Option<String>,
+    /// Whether the socket file exists, only checked for Unix domain sockets
+    pub socket_exists: Option<bool>,
+    pub mock_mode: bool,
+    pub connected: bool,
+    pub connect_error: Option<CommandError>,
+    pub core_version: Option<String>,
+    pub version_error: Option<CommandError>,
+    pub core: CoreStatusResponse,
+    /// Core executable locations in search order with whether each exists, or only the override when one is set
+    pub core_search_paths: Vec<String>,
+    /// Plain-text summary of the fields above, ready to paste into a support ticket
+    pub report: String,
+}
+
+/// Render the diagnostics as a text block for copying
+fn format_connection_diagnostics(diagnostics: &ConnectionDiagnostics) -> String {
+    let outcome = |ok: Option<&str>, err: &Option<CommandError>| match (ok, err) {
+        (_, Some(e)) => format!("failed: {}", e),
+        (Some(value), None) => value.to_string(),
+        (None, None) => "skipped".to_string(),
+    };
+
+    let mut lines = vec![
+        format!("ConnectTool GUI {}", diagnostics.app_version),
+        format!("OS: {} ({})", std::env::consts::OS, std::env::consts::ARCH),
+    ];
+    match &diagnostics.connection_mode {
+        ConnectionMode::Uds { path } => lines.push(format!("Connection: unix socket {}", path)),
+        ConnectionMode::Tcp { host, port } => lines.push(format!("Connection: tcp {}:{}", host, port)),
     }
-    
-    let mut guard = CORE_PROCESS.lock().unwrap();
-    
-    // Check if already running
-    if let Some(ref mut child) = *guard {
-        match child.try_wait() {
-            Ok(None) => {
-                // Already running
-                return Ok((true, Some(child.id())));
-            }
-            _ => {
-                // Process ended, clear it
-                *guard = None;
-            }
-        }
+    if let Some(exists) = diagnostics.socket_exists {
+        lines.push(format!("Socket file exists: {}", exists));
     }
-    
-    // Start the process
-    let child = Command::new(&core_path)
-        .current_dir(core_path.parent().unwrap_or(std::path::Path::new(".")))
-        .spawn()
-        .map_err(|e| format!("Failed to start ConnectToolCore: {}", e))?;
-    
-    let pid = child.id();
-    *guard = Some(child);
-    
-    // Wait a bit for the process to initialize
-    std::thread::sleep(std::time::Duration::from_millis(500));
-    
-    Ok((true, Some(pid)))
+    if diagnostics.mock_mode {
+        lines.push("Mock mode: enabled".to_string());
+    }
+    lines.push(format!(
+        "Connect: {}",
+        outcome(diagnostics.connected.then_some("ok"), &diagnostics.connect_error)
+    ));
+    lines.push(format!(
+        "GetVersion: {}",
+        outcome(diagnostics.core_version.as_deref(), &diagnostics.version_error)
+    ));
+    lines.push(format!("Core process: {}", diagnostics.core.message));
+    lines.push("Core search paths:".to_string());
+    for path in &diagnostics.core_search_paths {
+        lines.push(format!("  {}", path));
+    }
+    lines.join("\n")
 }
 
-/// Stop the ConnectToolCore process
-fn stop_core_process() -> Result<(), String> {
-    let mut guard = CORE_PROCESS.lock().unwrap();
-    
-    if let Some(ref mut child) = *guard {
-        // Try to kill the process
-        child.kill().map_err(|e| format!("Failed to kill ConnectToolCore: {}", e))?;
-        
-        // Wait for it to finish
-        let _ = child.wait();
-        
-        *guard = None;
-        Ok(())
+/// Connect afresh, bypassing the cached client, and report each step of the way
+#[tauri::command]
+#[tracing::instrument]
+async fn run_connection_diagnostics() -> Result<ConnectionDiagnostics, CommandError> {
+    let mode = connection_mode();
+    let socket_path = match &mode {
+        ConnectionMode::Uds { path } => Some(path.clone()),
+        ConnectionMode::Tcp { .. } => None,
+    };
+    let socket_exists = if cfg!(unix) {
+        socket_path.as_ref().map(|path| Path::new(path).exists())
+    } else {
+        None
+    };
+
+    let (mut connected, mut connect_failure, mut core_version, mut version_error) = (false, None, None, None);
+    if mock_mode() {
+        connected = true;
+        core_version = Some(mock::VERSION.to_string());
     } else {
-        Ok(()) // Already not running
+        match connect_client().await {
+            Ok(mut client) => {
+                connected = true;
+                match client.get_version(GetVersionRequest {}).await {
+                    Ok(response) => core_version = Some(response.into_inner().version),
+                    Err(status) => version_error = Some(rpc_error(status)),
+                }
+            }
+            Err(e) => connect_failure = Some(connect_error(&e)),
+        }
     }
+
+    let mut diagnostics = ConnectionDiagnostics {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        connection_mode: mode,
+        socket_path,
+        socket_exists,
+        mock_mode: mock_mode(),
+        connected,
+        connect_error: connect_failure,
+        core_version,
+        version_error,
+        core: get_core_status().await?,
+        core_search_paths: match core_path_override() {
+            Some(path) => vec![path],
+            None => core_search_paths(),
+        }
+        .iter()
+        .map(|path| {
+            let found = if path.is_file() { "found" } else { "missing" };
+            format!("{} ({})", path.display(), found)
+        })
+        .collect(),
+        report: String::new(),
+    };
+    diagnostics.report = format_connection_diagnostics(&diagnostics);
+    Ok(diagnostics)
+}
+
+/// Response structure for reset_all command
+#[derive(serde::Serialize)]
+pub struct ResetAllResponse {
+    pub core_stop: GracefulShutdownResult,
+    pub socket_removed: bool,
+    pub cancelled_requests: usize,
+    /// Background tasks that were stopped, e.g. "lobby_events"
+    pub stopped_tasks: Vec<String>,
+    pub message: String,
 }
 
 #[tauri::command]
-async fn get_core_status() -> Result<CoreStatusResponse, String> {
-    let (is_running, pid) = check_core_process_running();
-    
-    let message = if is_running {
-        format!("ConnectToolCore is running (PID: {})", pid.unwrap_or(0))
-    } else {
-        "ConnectToolCore is not running".to_string()
+#[tracing::instrument]
+async fn reset_all() -> Result<ResetAllResponse, CommandError> {
+    ensure_writable()?;
+    let _transition = CoreTransitionGuard::acquire()?;
+
+    let mut stopped_tasks = Vec::new();
+    for (name, stopped) in [
+        ("lobby_events", stop_lobby_events_task()),
+        ("vpn_status", stop_vpn_status_task()),
+        ("core_health", stop_core_health_task()),
+    ] {
+        if stopped {
+            stopped_tasks.push(name.to_string());
+        }
+    }
+
+    let cancelled_requests = {
+        let mut pending = PENDING_REQUESTS.lock().unwrap();
+        for token in pending.values() {
+            token.cancel();
+        }
+        let count = pending.len();
+        pending.clear();
+        count
     };
-    
-    Ok(CoreStatusResponse {
-        is_running,
-        pid,
-        message,
+
+    let core_stop = stop_core_process().await?;
+    reset_client();
+    let socket_removed = remove_stale_socket()?;
+
+    // Drop cached observations, persisted settings are kept
+    CORE_LOGS.lock().unwrap().clear();
+    CORE_HEALTH_HISTORY.lock().unwrap().clear();
+
+    tracing::info!(?stopped_tasks, cancelled_requests, socket_removed, "reset all app state");
+    Ok(ResetAllResponse {
+        message: format!(
+            "Core: {}, stale socket removed: {}, requests cancelled: {}, tasks stopped: {}",
+            match core_stop {
+                GracefulShutdownResult::NotRunning => "not running",
+                GracefulShutdownResult::Graceful => "stopped",
+                GracefulShutdownResult::Forced => "killed",
+            },
+            socket_removed,
+            cancelled_requests,
+            stopped_tasks.len()
+        ),
+        core_stop,
+        socket_removed,
+        cancelled_requests,
+        stopped_tasks,
     })
 }
 
+// Virtual adapters of VPN and LAN tools known to conflict with the core's adapter,
+// matched case-insensitively against adapter names and driver descriptions
+#[cfg(any(windows, test))]
+const CONFLICTING_NETWORK_TOOLS: &[&str] = &[
+    "Hamachi",
+    "ZeroTier",
+    "Radmin VPN",
+    "Tailscale",
+    "WireGuard",
+    "OpenVPN",
+    "TAP-Windows",
+    "SoftEther",
+    "NordLynx",
+    "Sakura",
+];
+
+/// A network adapter and whether it belongs to a tool known to conflict with the core
+#[derive(serde::Serialize)]
+pub struct NetworkToolEntry {
+    pub name: String,
+    pub description: String,
+    pub likely_conflict: bool,
+}
+
+/// Whether an adapter name or description matches CONFLICTING_NETWORK_TOOLS
+#[cfg(any(windows, test))]
+fn is_conflicting_network_tool(name: &str, description: &str) -> bool {
+    let name = name.to_lowercase();
+    let description = description.to_lowercase();
+    CONFLICTING_NETWORK_TOOLS.iter().any(|tool| {
+        let tool = tool.to_lowercase();
+        name.contains(&tool) || description.contains(&tool)
+    })
+}
+
+/// List network adapters with Get-NetAdapter, flagging the ones of conflicting tools
+#[cfg(windows)]
+fn list_network_adapters_windows() -> Result<Vec<NetworkToolEntry>, String> {
+    let output = run_powershell(
+        "Get-NetAdapter -IncludeHidden | Select-Object -Property Name, InterfaceDescription | ConvertTo-Json"
+    )?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("PowerShell command failed: {}", stderr));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // No adapters at all produce no output
+    if stdout.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let adapters = parse_powershell_json_list(&stdout, "network adapters")?;
+
+    Ok(adapters
+        .iter()
+        .map(|adapter| {
+            let field = |key: &str| adapter.get(key).and_then(|v| v.as_str()).unwrap_or("").to_string();
+            let name = field("Name");
+            let description = field("InterfaceDescription");
+            NetworkToolEntry {
+                likely_conflict: is_conflicting_network_tool(&name, &description),
+                name,
+                description,
+            }
+        })
+        .collect())
+}
+
+#[cfg(not(windows))]
+fn list_network_adapters_windows() -> Result<Vec<NetworkToolEntry>, String> {
+    Err("Network adapter detection is only supported on Windows".to_string())
+}
+
+/// Network adapters on this machine, flagging VPN tools that may conflict with the core's adapter
 #[tauri::command]
-async fn get_core_version() -> Result<GetVersionResponse, String> {
-    let mut client = get_client().await?;
-    let response = client
-        .get_version(GetVersionRequest {})
+#[tracing::instrument]
+async fn detect_conflicting_network_tools() -> Result<Vec<NetworkToolEntry>, CommandError> {
+    tauri::async_runtime::spawn_blocking(list_network_adapters_windows)
         .await
-        .map_err(|e| e.to_string())?;
-    Ok(response.into_inner())
+        .map_err(|e| format!("Failed to list network adapters: {}", e))?
+        .map_err(CommandError::from)
 }
 
-#[tauri::command]
-async fn start_core() -> Result<CoreControlResponse, String> {
-    match start_core_process() {
-        Ok((is_running, pid)) => Ok(CoreControlResponse {
-            success: true,
-            is_running,
-            pid,
-            message: "ConnectToolCore started successfully".to_string(),
-        }),
-        Err(e) => Ok(CoreControlResponse {
-            success: false,
-            is_running: false,
-            pid: None,
-            message: e,
-        }),
+// ============== End System Status ==============
+
+// How long window close may wait for the core to acknowledge leaving the lobby
+const EXIT_LEAVE_LOBBY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Best-effort leave of the current lobby, so an externally managed core doesn't keep us present.
+/// The last lobby stays saved for rejoin_last_lobby on the next launch.
+async fn leave_lobby_on_exit() {
+    let left = tokio::time::timeout(EXIT_LEAVE_LOBBY_TIMEOUT, async {
+        let mut client = get_client().await.ok()?;
+        let info = client.get_lobby_info(GetLobbyInfoRequest {}).await.ok()?.into_inner();
+        if !info.is_in_lobby {
+            return Some(false);
+        }
+        client.leave_lobby(LeaveLobbyRequest {}).await.ok()?;
+        Some(true)
+    })
+    .await;
+
+    match left {
+        Ok(Some(true)) => tracing::info!("left lobby on application exit"),
+        Ok(Some(false)) => {}
+        Ok(None) => tracing::warn!("failed to leave lobby on application exit"),
+        Err(_) => tracing::warn!("timed out leaving lobby on application exit"),
     }
 }
 
+// Upper bound for the GracefulThenKill timeout, the hidden app shouldn't linger for long after close
+const MAX_EXIT_GRACE_MS: u64 = 30_000;
+
+/// What happens to ConnectToolCore when the window is closed
+#[derive(Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ExitBehavior {
+    /// The default, as closing the window always killed the core
+    #[default]
+    KillImmediately,
+    /// Leave the lobby and ask the core to shut down, killing it after timeout_ms
+    GracefulThenKill { timeout_ms: u64 },
+    /// Keep the core running after the GUI is gone
+    LeaveRunning,
+}
+
 #[tauri::command]
-async fn stop_core() -> Result<CoreControlResponse, String> {
-    match stop_core_process() {
-        Ok(()) => Ok(CoreControlResponse {
-            success: true,
-            is_running: false,
-            pid: None,
-            message: "ConnectToolCore stopped successfully".to_string(),
-        }),
-        Err(e) => Ok(CoreControlResponse {
-            success: false,
-            is_running: true,
-            pid: None,
-            message: e,
-        }),
+#[tracing::instrument]
+async fn set_exit_behavior(mode: ExitBehavior) -> Result<ExitBehavior, CommandError> {
+    if let ExitBehavior::GracefulThenKill { timeout_ms } = mode {
+        if timeout_ms > MAX_EXIT_GRACE_MS {
+            return Err(format!("timeout_ms must be at most {}", MAX_EXIT_GRACE_MS).into());
+        }
     }
+    modify_settings(|s| s.exit_behavior = mode)?;
+    Ok(mode)
 }
 
-// ============== End ConnectToolCore Management ==============
+// Set once the close handler has started cleaning up, the exit that follows must not start it again
+static EXIT_CLEANUP_STARTED: AtomicBool = AtomicBool::new(false);
 
-/// Cleanup function to stop core process when application exits
-fn cleanup_core_on_exit() {
-    if let Ok(()) = stop_core_process() {
-        println!("ConnectToolCore stopped on application exit");
+/// Cleanup function to stop core process when application exits, as configured by set_exit_behavior
+async fn cleanup_core_on_exit() {
+    stop_vpn_status_task();
+    stop_lobby_events_task();
+    stop_core_health_task();
+
+    let graceful_timeout = match read_settings(|s| s.exit_behavior) {
+        ExitBehavior::LeaveRunning => {
+            tracing::info!("leaving ConnectToolCore running on application exit");
+            return;
+        }
+        ExitBehavior::KillImmediately => None,
+        ExitBehavior::GracefulThenKill { timeout_ms } => Some(Duration::from_millis(timeout_ms)),
+    };
+
+    // A stopped core can't leave the lobby anymore, so this comes first in every mode
+    leave_lobby_on_exit().await;
+    let result = match graceful_timeout {
+        None => run_core_blocking(kill_core_now).await,
+        Some(timeout) => stop_core_process_within(timeout).await,
+    };
+    match result {
+        Ok(GracefulShutdownResult::NotRunning) => {}
+        Ok(_) => tracing::info!("ConnectToolCore stopped on application exit"),
+        Err(e) => tracing::warn!(error = %e, "failed to stop ConnectToolCore on application exit"),
     }
 }
 
@@ -961,6 +6246,19 @@ fn cleanup_core_on_exit() {
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .setup(|app| {
+            init_logging(app.path().app_log_dir().ok());
+            let _ = APP_HANDLE.set(app.handle().clone());
+            if let Ok(dir) = app.path().app_config_dir() {
+                let _ = APP_CONFIG_DIR.set(dir);
+            }
+            if let Ok(dir) = app.path().app_data_dir() {
+                let _ = APP_DATA_DIR.set(dir);
+            }
+            load_settings();
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             create_lobby,
             join_lobby,
@@ -978,14 +6276,567 @@ pub fn run() {
             get_core_status,
             get_core_version,
             start_core,
-            stop_core
+            stop_core,
+            set_socket_path,
+            set_connection_timeouts,
+            ping_core,
+            subscribe_vpn_status,
+            unsubscribe_vpn_status,
+            set_core_autorestart,
+            get_core_logs,
+            restart_steam_normal,
+            get_steam_libraries,
+            set_firewall_profile,
+            add_core_firewall_rule,
+            remove_core_firewall_rule,
+            restore_firewall,
+            set_steam_path_override,
+            clear_steam_path_override,
+            restart_core,
+            start_core_with_args,
+            get_lobby_members,
+            kick_member,
+            subscribe_lobby_events,
+            unsubscribe_lobby_events,
+            cleanup_socket,
+            get_core_path_info,
+            set_core_path_override,
+            clear_core_path_override,
+            get_system_status,
+            invite_friends,
+            get_lobby_share_text,
+            copy_lobby_share_text,
+            get_vpn_routes,
+            cancel_request,
+            set_log_level,
+            is_elevated,
+            restart_steam_china_dryrun,
+            get_versions,
+            open_steam_folder,
+            open_core_folder,
+            rejoin_last_lobby,
+            get_connection_mode,
+            set_connection_mode,
+            set_tls_config,
+            clear_tls_config,
+            get_vpn_stats,
+            get_core_log_tail,
+            set_core_log_path_override,
+            clear_core_log_path_override,
+            list_profiles,
+            add_profile,
+            select_profile,
+            delete_profile,
+            normalize_steam_id,
+            cancel_steam_restart,
+            start_core_health_monitor,
+            stop_core_health_monitor,
+            get_core_health_history,
+            adopt_external_core,
+            reset_all,
+            get_lobby_capacity,
+            get_settings,
+            update_settings,
+            core_firewall_rule_exists,
+            get_core_config,
+            list_friend_lobbies,
+            set_mock_mode,
+            set_exit_behavior,
+            run_connection_diagnostics,
+            get_steam_accounts,
+            list_vpn_interfaces,
+            request_steam_restart_token,
+            set_core_console_visible,
+            get_core_capabilities,
+            list_vpn_regions,
+            set_vpn_region,
+            benchmark_socket,
+            set_readonly_mode,
+            steam_china_supported,
+            export_vpn_routing_table,
+            get_core_version_after_start,
+            detect_conflicting_network_tools
         ])
-        .on_window_event(|_window, event| {
-            if let tauri::WindowEvent::CloseRequested { .. } = event {
-                // Stop core process when the window is closed
-                cleanup_core_on_exit();
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                if EXIT_CLEANUP_STARTED.swap(true, Ordering::SeqCst) {
+                    return;
+                }
+                // Stopping the core can take the configured grace period, so the window goes
+                // away at once and the app exits when the cleanup is done
+                api.prevent_close();
+                let _ = window.hide();
+                let app = window.app_handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    cleanup_core_on_exit().await;
+                    app.exit(0);
+                });
             }
         })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile_states(status: &FirewallStatusResponse) -> Vec<(&str, bool)> {
+        status
+            .profiles
+            .iter()
+            .map(|profile| (profile.name.as_str(), profile.enabled))
+            .collect()
+    }
+
+    #[test]
+    fn firewall_json_array() {
+        let json = r#"[
+            {"Name":"Domain","Enabled":true},
+            {"Name":"Private","Enabled":false},
+            {"Name":"Public","Enabled":true}
+        ]"#;
+        let status = parse_firewall_profiles_json(json).unwrap();
+        assert!(status.domain_enabled);
+        assert!(!status.private_enabled);
+        assert!(status.public_enabled);
+        assert_eq!(
+            profile_states(&status),
+            [("Domain", true), ("Private", false), ("Public", true)]
+        );
+    }
+
+    #[test]
+    fn firewall_json_single_object() {
+        // ConvertTo-Json unwraps a one-element pipeline into a bare object
+        let json = "{\r\n  \"Name\": \"Public\",\r\n  \"Enabled\": true\r\n}\r\n";
+        let status = parse_firewall_profiles_json(json).unwrap();
+        assert!(status.public_enabled);
+        assert!(!status.domain_enabled);
+        assert_eq!(profile_states(&status), [("Public", true)]);
+    }
+
+    #[test]
+    fn firewall_json_enabled_as_string() {
+        let json = r#"[
+            {"Name":"Domain","Enabled":"True"},
+            {"Name":"Private","Enabled":"False"},
+            {"Name":"Public","Enabled":"true"}
+        ]"#;
+        let status = parse_firewall_profiles_json(json).unwrap();
+        assert!(status.domain_enabled);
+        assert!(!status.private_enabled);
+        assert!(status.public_enabled);
+    }
+
+    #[test]
+    fn firewall_json_enabled_as_number() {
+        // Windows PowerShell serializes the GpoBoolean enum by value
+        let json = r#"[{"Name":"Domain","Enabled":1},{"Name":"Private","Enabled":0},{"Name":"Public"}]"#;
+        let status = parse_firewall_profiles_json(json).unwrap();
+        assert!(status.domain_enabled);
+        assert!(!status.private_enabled);
+        assert!(!status.public_enabled);
+    }
+
+    #[test]
+    fn firewall_json_keeps_unknown_profiles() {
+        let status = parse_firewall_profiles_json(r#"[{"Name":"Custom","Enabled":true}]"#).unwrap();
+        assert!(!status.domain_enabled && !status.private_enabled && !status.public_enabled);
+        assert_eq!(profile_states(&status), [("Custom", true)]);
+    }
+
+    #[test]
+    fn powershell_json_accepts_a_single_object() {
+        let items = parse_powershell_json_list(r#"{"Name":"Ethernet"}"#, "network adapters").unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["Name"], "Ethernet");
+        assert!(parse_powershell_json_list("42", "network adapters").is_err());
+    }
+
+    #[test]
+    fn conflicting_tools_match_real_adapter_descriptions() {
+        let conflicting = [
+            ("Hamachi", "LogMeIn Hamachi Virtual Ethernet Adapter"),
+            ("ZeroTier One [8056c2e21c000001]", "ZeroTier Virtual Port"),
+            ("Ethernet 3", "TAP-Windows Adapter V9"),
+            ("wg0", "WireGuard Tunnel"),
+            ("Radmin VPN", "Famatech Radmin VPN Ethernet Adapter"),
+            ("Tailscale", "Tailscale Tunnel"),
+        ];
+        for (name, description) in conflicting {
+            assert!(is_conflicting_network_tool(name, description), "{}", description);
+        }
+
+        let unrelated = [
+            ("Ethernet", "Intel(R) Ethernet Connection (7) I219-V"),
+            ("Ethernet 2", "Realtek PCIe GbE Family Controller"),
+            ("Wi-Fi", "Intel(R) Wi-Fi 6 AX201 160MHz"),
+            ("vEthernet (Default Switch)", "Hyper-V Virtual Ethernet Adapter"),
+        ];
+        for (name, description) in unrelated {
+            assert!(!is_conflicting_network_tool(name, description), "{}", description);
+        }
+    }
+
+    #[test]
+    fn firewall_json_rejects_invalid_output() {
+        assert!(parse_firewall_profiles_json("").is_err());
+        assert!(parse_firewall_profiles_json("Get-NetFirewallProfile : Access is denied").is_err());
+        assert!(parse_firewall_profiles_json("\"True\"").is_err());
+    }
+
+    /// Serializes tests that touch the core process globals and puts them back on drop,
+    /// including when an assertion fails
+    struct CoreProcessTestGuard {
+        _serial: tokio::sync::MutexGuard<'static, ()>,
+        dir: PathBuf,
+    }
+
+    impl CoreProcessTestGuard {
+        async fn acquire() -> Self {
+            static SERIAL: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+            CoreProcessTestGuard {
+                _serial: SERIAL.lock().await,
+                dir: std::env::temp_dir().join(format!("connect-tool-core-test-{}", std::process::id())),
+            }
+        }
+
+        /// Point the core path override at a stand-in core that stays up until it is killed
+        #[cfg(unix)]
+        fn install_fake_core(&self) {
+            use std::os::unix::fs::PermissionsExt;
+
+            std::fs::create_dir_all(&self.dir).unwrap();
+            let core = self.dir.join(CORE_EXECUTABLE_NAME);
+            std::fs::write(&core, "#!/bin/sh\nexec sleep 30\n").unwrap();
+            std::fs::set_permissions(&core, std::fs::Permissions::from_mode(0o755)).unwrap();
+            // Set in memory only, tests have no app config directory to save to
+            SETTINGS.lock().unwrap().core_path_override = Some(core);
+        }
+    }
+
+    impl Drop for CoreProcessTestGuard {
+        fn drop(&mut self) {
+            // Never leave a stand-in core running
+            let mut slot = CORE_PROCESS.lock().unwrap_or_else(|e| e.into_inner());
+            let _ = kill_core_locked(&mut slot);
+            MANAGED_CORE_PID.store(0, Ordering::SeqCst);
+            CORE_SHOULD_RUN.store(false, Ordering::SeqCst);
+            SETTINGS.lock().unwrap_or_else(|e| e.into_inner()).core_path_override = None;
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn core_status_answers_while_kill_is_blocked() {
+        let _guard = CoreProcessTestGuard::acquire().await;
+
+        // Stand in for a kill stuck waiting on the OS: hold CORE_PROCESS on the blocking pool
+        let (locked_tx, locked_rx) = tokio::sync::oneshot::channel();
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+        let holder = tokio::task::spawn_blocking(move || {
+            let _slot = CORE_PROCESS.lock().unwrap();
+            MANAGED_CORE_PID.store(4242, Ordering::SeqCst);
+            let _ = locked_tx.send(());
+            // Also lets go if the test fails and drops the sender
+            let _ = release_rx.recv_timeout(Duration::from_secs(5));
+        });
+        locked_rx.await.unwrap();
+
+        let status = tokio::time::timeout(Duration::from_secs(1), get_core_status())
+            .await
+            .expect("get_core_status waited for the blocked kill")
+            .unwrap();
+        assert!(status.is_running);
+        assert_eq!(status.pid, Some(4242));
+
+        release_tx.send(()).unwrap();
+        holder.await.unwrap();
+    }
+
+    #[test]
+    fn login_users_marks_most_recent_account() {
+        let content = r#"
+            "users"
+            {
+                "76561197960287930"
+                {
+                    "AccountName"   "first_account"
+                    "PersonaName"   "First"
+                    "RememberPassword"  "1"
+                    "MostRecent"    "0"
+                }
+                "76561197960287931"
+                {
+                    "AccountName"   "second_account"
+                    "PersonaName"   ""
+                    "mostrecent"    "1"
+                }
+            }
+        "#;
+        let accounts = parse_login_users(content).unwrap();
+        let summary: Vec<_> = accounts
+            .iter()
+            .map(|a| (a.steam_id64.as_str(), a.account_name.as_str(), a.persona_name.as_deref(), a.most_recent))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("76561197960287930", "first_account", Some("First"), false),
+                ("76561197960287931", "second_account", None, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn login_users_skips_malformed_entries() {
+        let content = r#"
+            "users"
+            {
+                "not-a-steam-id" { "AccountName" "ignored" }
+                "76561197960287930" { "PersonaName" "No account name" }
+                "76561197960287931" { "AccountName" "kept" }
+            }
+        "#;
+        let accounts = parse_login_users(content).unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].account_name, "kept");
+        assert!(!accounts[0].most_recent);
+    }
+
+    #[test]
+    fn login_users_rejects_files_without_users() {
+        assert!(parse_login_users(r#""config" { }"#).is_err());
+        assert!(parse_login_users(r#""users" { "#).is_err());
+    }
+
+    #[test]
+    fn firewall_error_detects_access_denied_in_any_language() {
+        let cases = [
+            "Set-NetFirewallProfile : Access is denied.",
+            "Set-NetFirewallProfile : 拒绝访问。",
+            "Set-NetFirewallProfile : 存取被拒。",
+            "Set-NetFirewallProfile : Zugriff verweigert",
+            "Set-NetFirewallProfile : Accès refusé.",
+            "Set-NetFirewallProfile : Acceso denegado.",
+            "Set-NetFirewallProfile : アクセスが拒否されました。",
+            "Set-NetFirewallProfile : Отказано в доступе.",
+            // Unknown language, still identified by the HRESULT and error category
+            "Set-NetFirewallProfile : Pääsy estetty. (HRESULT: 0x80070005)",
+            "CategoryInfo : PermissionDenied: (MSFT_NetFirewallProfile) [Set-NetFirewallProfile]",
+        ];
+        for raw in cases {
+            assert_eq!(classify_firewall_error(raw), FirewallErrorCode::AccessDenied, "{}", raw);
+        }
+    }
+
+    #[test]
+    fn firewall_error_prefers_policy_over_access_denied() {
+        let cases = [
+            "Access is denied. This setting is enforced by Group Policy.",
+            "拒绝访问。此设置由组策略管理。",
+            "Zugriff verweigert. Die Einstellung wird per Gruppenrichtlinie verwaltet.",
+            "Some settings are managed by your organization.",
+        ];
+        for raw in cases {
+            assert_eq!(classify_firewall_error(raw), FirewallErrorCode::BlockedByPolicy, "{}", raw);
+        }
+    }
+
+    #[test]
+    fn firewall_error_falls_back_to_unknown() {
+        assert_eq!(classify_firewall_error(""), FirewallErrorCode::Unknown);
+        assert_eq!(classify_firewall_error("firewall query timed out"), FirewallErrorCode::Unknown);
+        assert_eq!(classify_firewall_error("The RPC server is unavailable. (0x800706BA)"), FirewallErrorCode::Unknown);
+    }
+
+    /// File in the temp directory, removed on drop
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn with_content(name: &str, content: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("connect-tool-{}-{}", std::process::id(), name));
+            std::fs::write(&path, content).unwrap();
+            TempFile(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn file_tail_of_short_file_returns_every_line() {
+        let file = TempFile::with_content("tail-short.log", "first\nsecond\n");
+        assert_eq!(read_file_tail(&file.0, 10).unwrap(), ["first", "second"]);
+    }
+
+    #[test]
+    fn file_tail_keeps_only_the_last_lines() {
+        let file = TempFile::with_content("tail-last.log", "1\n2\n3\n4\n5");
+        assert_eq!(read_file_tail(&file.0, 2).unwrap(), ["4", "5"]);
+        assert!(read_file_tail(&file.0, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn file_tail_spans_read_chunks() {
+        // Around 40 KB, so the tail is assembled from several backwards reads
+        let content: String = (0..4000).map(|i| format!("line {:05}\r\n", i)).collect();
+        let file = TempFile::with_content("tail-chunks.log", &content);
+        let tail = read_file_tail(&file.0, 1500).unwrap();
+        assert_eq!(tail.len(), 1500);
+        assert_eq!(tail.first().map(String::as_str), Some("line 02500"));
+        assert_eq!(tail.last().map(String::as_str), Some("line 03999"));
+    }
+
+    #[test]
+    fn file_tail_handles_empty_and_missing_files() {
+        let file = TempFile::with_content("tail-empty.log", "");
+        assert!(read_file_tail(&file.0, 5).unwrap().is_empty());
+
+        let missing = std::env::temp_dir().join("connect-tool-no-such-file.log");
+        assert!(read_file_tail(&missing, 5).unwrap_err().starts_with("Failed to open"));
+    }
+
+    fn snapshot(lobby_id: Option<&str>, members: &[&str]) -> LobbySnapshot {
+        LobbySnapshot {
+            lobby_id: lobby_id.map(str::to_string),
+            members: members.iter().map(|id| id.to_string()).collect(),
+        }
+    }
+
+    fn lobby_events(prev: &LobbySnapshot, next: &LobbySnapshot) -> Vec<(LobbyEventKind, Option<String>, String)> {
+        diff_lobby_snapshots(prev, next)
+            .into_iter()
+            .map(|event| (event.kind, event.steam_id, event.lobby_id))
+            .collect()
+    }
+
+    #[test]
+    fn lobby_diff_reports_member_join() {
+        let prev = snapshot(Some("100"), &["1"]);
+        let next = snapshot(Some("100"), &["1", "2"]);
+        assert_eq!(
+            lobby_events(&prev, &next),
+            [(LobbyEventKind::MemberJoined, Some("2".to_string()), "100".to_string())]
+        );
+    }
+
+    #[test]
+    fn lobby_diff_reports_member_leave() {
+        let prev = snapshot(Some("100"), &["1", "2", "3"]);
+        let next = snapshot(Some("100"), &["1", "3"]);
+        assert_eq!(
+            lobby_events(&prev, &next),
+            [(LobbyEventKind::MemberLeft, Some("2".to_string()), "100".to_string())]
+        );
+    }
+
+    #[test]
+    fn lobby_diff_reports_lobby_switch_without_member_events() {
+        let prev = snapshot(Some("100"), &["1", "2"]);
+        let next = snapshot(Some("200"), &["1", "3"]);
+        assert_eq!(
+            lobby_events(&prev, &next),
+            [
+                (LobbyEventKind::LobbyClosed, None, "100".to_string()),
+                (LobbyEventKind::LobbyJoined, None, "200".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn lobby_diff_reports_joining_and_leaving_the_lobby() {
+        let outside = snapshot(None, &[]);
+        let inside = snapshot(Some("100"), &["1", "2"]);
+        assert_eq!(
+            lobby_events(&outside, &inside),
+            [(LobbyEventKind::LobbyJoined, None, "100".to_string())]
+        );
+        assert_eq!(
+            lobby_events(&inside, &outside),
+            [(LobbyEventKind::LobbyClosed, None, "100".to_string())]
+        );
+    }
+
+    #[test]
+    fn lobby_diff_is_empty_without_changes() {
+        let lobby = snapshot(Some("100"), &["1", "2"]);
+        assert!(lobby_events(&lobby, &snapshot(Some("100"), &["2", "1"])).is_empty());
+        assert!(lobby_events(&snapshot(None, &[]), &snapshot(None, &[])).is_empty());
+    }
+
+    #[test]
+    fn core_features_are_learned_from_rpc_outcomes() {
+        let rpc = |code: tonic::Code| -> Result<(), CommandError> {
+            Err(CommandError::Rpc {
+                code: code as i32,
+                message: String::new(),
+            })
+        };
+        let _ = record_core_feature("kick_member", rpc(tonic::Code::Unimplemented));
+        let _ = record_core_feature("vpn_regions", rpc(tonic::Code::FailedPrecondition));
+        let _ = record_core_feature("lobby", Ok(()));
+        let _ = record_core_feature::<()>("invite_friend", Err(CommandError::Timeout));
+
+        let support = CORE_FEATURE_SUPPORT.lock().unwrap();
+        assert_eq!(support.get("kick_member"), Some(&false));
+        assert_eq!(support.get("vpn_regions"), Some(&true));
+        assert_eq!(support.get("lobby"), Some(&true));
+        assert_eq!(support.get("invite_friend"), None);
+    }
+
+    #[test]
+    fn leave_ignores_only_not_in_lobby_errors() {
+        let rpc = |code: tonic::Code, message: &str| CommandError::Rpc {
+            code: code as i32,
+            message: message.to_string(),
+        };
+        assert!(is_not_in_lobby_error(&rpc(tonic::Code::FailedPrecondition, "Not in lobby")));
+        assert!(is_not_in_lobby_error(&rpc(tonic::Code::Unknown, "user is not in a lobby")));
+        assert!(!is_not_in_lobby_error(&rpc(tonic::Code::FailedPrecondition, "Steam is not running")));
+        assert!(!is_not_in_lobby_error(&CommandError::Timeout));
+    }
+
+    // A single runtime thread deadlocks if a core operation blocks it while holding CORE_PROCESS
+    #[cfg(unix)]
+    #[tokio::test(flavor = "current_thread")]
+    async fn core_process_survives_concurrent_start_stop_status() {
+        let guard = CoreProcessTestGuard::acquire().await;
+        guard.install_fake_core();
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for i in 0..30 {
+            tasks.spawn(async move {
+                match i % 3 {
+                    0 => run_core_blocking(|| start_core_process(CoreLaunchOptions::default()))
+                        .await
+                        .map(|_| ()),
+                    1 => stop_core_process_within(Duration::ZERO).await.map(|_| ()),
+                    _ => get_core_status().await.map(|_| ()).map_err(|e| e.to_string()),
+                }
+            });
+        }
+
+        let results = tokio::time::timeout(Duration::from_secs(30), async {
+            let mut results = Vec::new();
+            while let Some(result) = tasks.join_next().await {
+                results.push(result.unwrap());
+            }
+            results
+        })
+        .await
+        .expect("concurrent core operations deadlocked");
+        for result in results {
+            result.unwrap();
+        }
+
+        stop_core_process_within(Duration::ZERO).await.unwrap();
+        assert_eq!(check_core_process_running(), (false, None));
+        assert_eq!(MANAGED_CORE_PID.load(Ordering::SeqCst), 0);
+        assert!(!get_core_status().await.unwrap().managed);
+    }
+}
@@ -0,0 +1,141 @@
+// Canned ConnectToolCore responses for mock mode, so the GUI can be developed
+// without the core binary. Everything here is deterministic.
+
+use crate::connecttool::*;
+use std::net::Ipv4Addr;
+
+pub const VERSION: &str = "0.1.0+mock";
+pub const LOBBY_ID: &str = "109775241021923456";
+
+pub fn version() -> GetVersionResponse {
+    GetVersionResponse {
+        version: VERSION.to_string(),
+    }
+}
+
+pub fn create_lobby() -> CreateLobbyResponse {
+    CreateLobbyResponse {
+        success: true,
+        lobby_id: LOBBY_ID.to_string(),
+    }
+}
+
+pub fn join_lobby(lobby_id: &str) -> JoinLobbyResponse {
+    JoinLobbyResponse {
+        success: true,
+        message: format!("Joined mock lobby {}", lobby_id),
+    }
+}
+
+pub fn leave_lobby() -> LeaveLobbyResponse {
+    LeaveLobbyResponse { success: true }
+}
+
+fn member(steam_id: &str, name: &str, ping: i32, relay_info: &str) -> LobbyMember {
+    LobbyMember {
+        steam_id: steam_id.to_string(),
+        name: name.to_string(),
+        ping,
+        relay_info: relay_info.to_string(),
+    }
+}
+
+pub fn lobby_info() -> GetLobbyInfoResponse {
+    GetLobbyInfoResponse {
+        is_in_lobby: true,
+        lobby_id: LOBBY_ID.to_string(),
+        members: vec![
+            member("76561198000000001", "Mock Host", 0, "local"),
+            member("76561198000000002", "Mock Peer", 42, "direct"),
+            member("76561198000000003", "Mock Relayed Peer", 118, "relay:hkg"),
+        ],
+    }
+}
+
+pub fn friend_lobbies() -> GetFriendLobbiesResponse {
+    GetFriendLobbiesResponse {
+        lobbies: vec![
+            FriendLobby {
+                steam_id: "76561198000000011".to_string(),
+                name: "Mock Friend".to_string(),
+                lobby_id: "109775241021900011".to_string(),
+            },
+            FriendLobby {
+                steam_id: "76561198000000012".to_string(),
+                name: "Another Mock Friend".to_string(),
+                lobby_id: "109775241021900012".to_string(),
+            },
+        ],
+    }
+}
+
+pub fn invite_friend() -> InviteFriendResponse {
+    InviteFriendResponse { success: true }
+}
+
+pub fn kick_member() -> KickMemberResponse {
+    KickMemberResponse {
+        success: true,
+        error: KickMemberError::None as i32,
+    }
+}
+
+pub fn vpn_status() -> GetVpnStatusResponse {
+    GetVpnStatusResponse {
+        enabled: true,
+        local_ip: "10.147.0.1".to_string(),
+        device_name: "mock0".to_string(),
+        stats: Some(VpnStats {
+            packets_sent: 1200,
+            bytes_sent: 1_048_576,
+            packets_received: 980,
+            bytes_received: 786_432,
+            packets_dropped: 3,
+        }),
+    }
+}
+
+fn route(ip: [u8; 4], name: &str, is_local: bool) -> VpnRoute {
+    VpnRoute {
+        ip: u32::from(Ipv4Addr::from(ip)),
+        name: name.to_string(),
+        is_local,
+    }
+}
+
+pub fn vpn_routing_table() -> GetVpnRoutingTableResponse {
+    GetVpnRoutingTableResponse {
+        routes: vec![
+            route([10, 147, 0, 1], "Mock Host", true),
+            route([10, 147, 0, 2], "Mock Peer", false),
+            route([10, 147, 0, 3], "Mock Relayed Peer", false),
+        ],
+    }
+}
+
+fn region(id: &str, name: &str, ping_ms: i32) -> VpnRegion {
+    VpnRegion {
+        id: id.to_string(),
+        name: name.to_string(),
+        ping_ms,
+    }
+}
+
+pub fn vpn_regions() -> ListVpnRegionsResponse {
+    ListVpnRegionsResponse {
+        regions: vec![
+            region("hkg", "Hong Kong", 38),
+            region("sgp", "Singapore", 72),
+            region("tyo", "Tokyo", 55),
+            region("fra", "Frankfurt", -1),
+        ],
+        current_region: "hkg".to_string(),
+    }
+}
+
+pub fn set_vpn_region(region: &str) -> SetVpnRegionResponse {
+    SetVpnRegionResponse {
+        success: true,
+        message: format!("Mock VPN now routed through {}", region),
+    }
+}
@@ -0,0 +1,145 @@
+// Parsing of the SteamID notations users paste: SteamID64, SteamID3, SteamID2
+// and steamcommunity.com profile URLs
+
+// SteamID64 of account ID 0 for an individual account in the public universe
+const STEAM_ID64_BASE: u64 = 76561197960265728;
+
+const ACCEPTED_FORMATS: &str =
+    "expected a SteamID64 (7656119...), SteamID3 ([U:1:123]), SteamID2 (STEAM_0:1:123) \
+     or a steamcommunity.com/profiles/ URL";
+
+fn invalid(input: &str) -> String {
+    format!("Invalid Steam ID \"{}\": {}", input, ACCEPTED_FORMATS)
+}
+
+fn from_account_id(account_id: u64) -> u64 {
+    STEAM_ID64_BASE + account_id
+}
+
+fn parse_steam_id64(value: &str) -> Option<u64> {
+    if value.len() != 17 || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let id = value.parse::<u64>().ok()?;
+    // Only individual accounts can be invited
+    (id > STEAM_ID64_BASE && id - STEAM_ID64_BASE <= u32::MAX as u64).then_some(id)
+}
+
+/// [U:1:22202], brackets optional
+fn parse_steam_id3(value: &str) -> Option<u64> {
+    let inner = value.trim_start_matches('[').trim_end_matches(']');
+    let mut parts = inner.split(':');
+    let kind = parts.next()?;
+    let universe = parts.next()?;
+    let account_id = parts.next()?.parse::<u32>().ok()?;
+    if parts.next().is_some() || !kind.eq_ignore_ascii_case("U") || universe != "1" {
+        return None;
+    }
+    Some(from_account_id(account_id as u64))
+}
+
+/// STEAM_X:Y:Z, where the account ID is Z * 2 + Y
+fn parse_steam_id2(value: &str) -> Option<u64> {
+    let rest = value.get(..6)?.eq_ignore_ascii_case("STEAM_").then(|| &value[6..])?;
+    let mut parts = rest.split(':');
+    let _universe = parts.next()?.parse::<u8>().ok()?;
+    let low = parts.next()?.parse::<u64>().ok().filter(|&y| y <= 1)?;
+    let high = parts.next()?.parse::<u64>().ok()?;
+    if parts.next().is_some() || high * 2 + low > u32::MAX as u64 {
+        return None;
+    }
+    Some(from_account_id(high * 2 + low))
+}
+
+/// The ID segment of a steamcommunity.com profile URL
+fn profile_url_segment<'a>(value: &'a str, prefix: &str) -> Option<&'a str> {
+    let value = value
+        .strip_prefix("https://")
+        .or_else(|| value.strip_prefix("http://"))
+        .unwrap_or(value);
+    let value = value.strip_prefix("www.").unwrap_or(value);
+    let rest = value.strip_prefix("steamcommunity.com/")?.strip_prefix(prefix)?;
+    rest.split(['/', '?', '#']).next().filter(|segment| !segment.is_empty())
+}
+
+/// Detect the notation of a Steam ID and convert it to SteamID64
+pub fn normalize(input: &str) -> Result<String, String> {
+    let value = input.trim();
+
+    if profile_url_segment(value, "id/").is_some() {
+        return Err(format!(
+            "Custom profile URLs can't be resolved offline, use the profile's SteamID64: {}",
+            ACCEPTED_FORMATS
+        ));
+    }
+    let value = profile_url_segment(value, "profiles/").unwrap_or(value);
+
+    parse_steam_id64(value)
+        .or_else(|| parse_steam_id3(value))
+        .or_else(|| parse_steam_id2(value))
+        .map(|id| id.to_string())
+        .ok_or_else(|| invalid(input.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_each_notation() {
+        let cases = [
+            ("76561197960287930", "76561197960287930"),
+            ("  76561197960287930\n", "76561197960287930"),
+            ("[U:1:22202]", "76561197960287930"),
+            ("u:1:22202", "76561197960287930"),
+            ("STEAM_0:0:11101", "76561197960287930"),
+            ("steam_1:1:11101", "76561197960287931"),
+            ("https://steamcommunity.com/profiles/76561197960287930", "76561197960287930"),
+            ("http://www.steamcommunity.com/profiles/76561197960287930/", "76561197960287930"),
+            ("steamcommunity.com/profiles/[U:1:22202]?l=schinese", "76561197960287930"),
+            // Largest account ID that fits in 32 bits
+            ("[U:1:4294967295]", "76561202255233023"),
+            ("STEAM_0:1:2147483647", "76561202255233023"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(normalize(input).as_deref(), Ok(expected), "input {:?}", input);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        let cases = [
+            "",
+            "not a steam id",
+            // SteamID64 of account 0, and one past the 32-bit account range
+            "76561197960265728",
+            "76561202255233024",
+            // Wrong length or non-digits
+            "7656119796028793",
+            "7656119796028793a",
+            // SteamID3 outside the public universe or not an individual account
+            "[U:2:22202]",
+            "[G:1:22202]",
+            "[U:1:-5]",
+            "[U:1:4294967296]",
+            "[U:1:22202:1]",
+            // SteamID2 with an out-of-range universe, Y other than 0/1 or a bad account ID
+            "STEAM_256:0:11101",
+            "STEAM_0:2:11101",
+            "STEAM_0:1:abc",
+            "STEAM_0:1:2147483648",
+            "STEAM_0:1",
+            "https://steamcommunity.com/profiles/",
+        ];
+        for input in cases {
+            let err = normalize(input).unwrap_err();
+            assert!(err.starts_with("Invalid Steam ID"), "input {:?}: {}", input, err);
+        }
+    }
+
+    #[test]
+    fn rejects_custom_profile_urls() {
+        let err = normalize("https://steamcommunity.com/id/gabelogannewell").unwrap_err();
+        assert!(err.starts_with("Custom profile URLs can't be resolved offline"));
+    }
+}
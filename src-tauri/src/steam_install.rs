@@ -0,0 +1,481 @@
+// Steam install and executable resolution. The probes work against an injected
+// filesystem and injected registry/command results so every platform branch can
+// be exercised from tests on any host.
+
+use std::path::{Path, PathBuf};
+
+/// The filesystem queries made while resolving Steam paths
+pub trait FileSystem {
+    fn exists(&self, path: &Path) -> bool;
+    #[cfg(any(target_os = "linux", test))]
+    fn is_file(&self, path: &Path) -> bool;
+    #[cfg(any(target_os = "linux", test))]
+    fn read_link(&self, path: &Path) -> Option<PathBuf>;
+}
+
+/// The host filesystem
+pub struct RealFileSystem;
+
+impl FileSystem for RealFileSystem {
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    #[cfg(any(target_os = "linux", test))]
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    #[cfg(any(target_os = "linux", test))]
+    fn read_link(&self, path: &Path) -> Option<PathBuf> {
+        std::fs::read_link(path).ok()
+    }
+}
+
+/// Registry hive holding a Steam install path value
+#[cfg(any(windows, test))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RegistryRoot {
+    CurrentUser,
+    LocalMachine,
+}
+
+/// A registry value that may hold the Steam install path
+#[cfg(any(windows, test))]
+#[derive(Debug)]
+pub struct RegistryValue {
+    pub root: RegistryRoot,
+    pub key: &'static str,
+    pub name: &'static str,
+    /// Reported as the detection source when this value matches
+    pub source: &'static str,
+}
+
+/// Registry values checked on Windows, in order
+#[cfg(any(windows, test))]
+pub const WINDOWS_REGISTRY_VALUES: [RegistryValue; 3] = [
+    RegistryValue {
+        root: RegistryRoot::CurrentUser,
+        key: "Software\\Valve\\Steam",
+        name: "SteamPath",
+        source: "HKCU",
+    },
+    RegistryValue {
+        root: RegistryRoot::LocalMachine,
+        key: "SOFTWARE\\Valve\\Steam",
+        name: "InstallPath",
+        source: "HKLM",
+    },
+    // The 32-bit registry view on 64-bit Windows, where the 32-bit Steam installer writes
+    RegistryValue {
+        root: RegistryRoot::LocalMachine,
+        key: "SOFTWARE\\WOW6432Node\\Valve\\Steam",
+        name: "InstallPath",
+        source: "HKLM-WOW6432Node",
+    },
+];
+
+/// Find the Steam install on Windows from the registry, the Program Files
+/// directories, then a scan of every drive
+#[cfg(any(windows, test))]
+pub fn find_windows(
+    read_registry: impl Fn(&RegistryValue) -> Option<String>,
+    program_files: &[PathBuf],
+    fs: &impl FileSystem,
+) -> Option<(PathBuf, String)> {
+    for value in &WINDOWS_REGISTRY_VALUES {
+        if let Some(path) = read_registry(value) {
+            let steam_path = PathBuf::from(&path);
+            if fs.exists(&steam_path) {
+                return Some((steam_path, value.source.to_string()));
+            }
+        }
+    }
+
+    // Fallback: check default locations
+    for dir in program_files {
+        let steam_path = dir.join("Steam");
+        if fs.exists(&steam_path.join("steam.exe")) {
+            let source = format!("default:{}", steam_path.display());
+            return Some((steam_path, source));
+        }
+    }
+
+    // Last resort: look for a Steam root on every drive, skipping the legacy floppy letters
+    for letter in b'C'..=b'Z' {
+        let drive = PathBuf::from(format!("{}:\\", letter as char));
+        if !fs.exists(&drive) {
+            continue;
+        }
+        for dir in ["Steam", "Program Files (x86)\\Steam", "Program Files\\Steam"] {
+            let steam_path = drive.join(dir);
+            if fs.exists(&steam_path.join("steam.exe")) {
+                let source = format!("drive:{}", steam_path.display());
+                return Some((steam_path, source));
+            }
+        }
+    }
+
+    None
+}
+
+/// Find the Steam app bundle on macOS from Spotlight results, then the default locations
+#[cfg(any(target_os = "macos", test))]
+pub fn find_macos(
+    mdfind_results: &[PathBuf],
+    home: Option<&Path>,
+    fs: &impl FileSystem,
+) -> Option<(PathBuf, String)> {
+    for steam_path in mdfind_results {
+        if fs.exists(steam_path) {
+            return Some((steam_path.clone(), "mdfind".to_string()));
+        }
+    }
+
+    let default_path = PathBuf::from("/Applications/Steam.app");
+    if fs.exists(&default_path) {
+        let source = format!("default:{}", default_path.display());
+        return Some((default_path, source));
+    }
+
+    if let Some(home) = home {
+        let user_path = home.join("Applications/Steam.app");
+        if fs.exists(&user_path) {
+            let source = format!("default:{}", user_path.display());
+            return Some((user_path, source));
+        }
+    }
+
+    None
+}
+
+/// Find the Steam install on Linux: the Steam Deck location, the `steam`
+/// found on PATH, then the common install directories
+#[cfg(any(target_os = "linux", test))]
+pub fn find_linux(
+    is_steam_deck: bool,
+    home: Option<&Path>,
+    which_steam: Option<PathBuf>,
+    fs: &impl FileSystem,
+) -> Option<(PathBuf, String)> {
+    // The Deck ships Steam in the deck user's home, also when HOME points elsewhere
+    if is_steam_deck {
+        let home = home.unwrap_or(Path::new("/home/deck"));
+        let candidates = [home.join(".steam/steam"), PathBuf::from("/home/deck/.steam/steam")];
+        for path in candidates {
+            if fs.exists(&path) {
+                let source = format!("steamdeck:{}", path.display());
+                return Some((path, source));
+            }
+        }
+    }
+
+    if let Some(steam_path) = which_steam {
+        if fs.exists(&steam_path) {
+            // Get the actual Steam installation directory
+            if let Some(real_path) = fs.read_link(&steam_path) {
+                if let Some(parent) = real_path.parent() {
+                    return Some((parent.to_path_buf(), "which".to_string()));
+                }
+            }
+            return Some((steam_path, "which".to_string()));
+        }
+    }
+
+    // Check common paths
+    if let Some(home) = home {
+        let paths = [
+            home.join(".steam/steam"),
+            home.join(".steam"),
+            home.join(".local/share/Steam"),
+            PathBuf::from("/usr/share/steam"),
+            PathBuf::from("/usr/lib/steam"),
+        ];
+
+        for path in paths {
+            if fs.exists(&path) {
+                let source = format!("default:{}", path.display());
+                return Some((path, source));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(any(windows, test))]
+pub fn exe_path_windows(steam_path: &Path, fs: &impl FileSystem) -> Option<PathBuf> {
+    let exe = steam_path.join("steam.exe");
+    fs.exists(&exe).then_some(exe)
+}
+
+#[cfg(any(target_os = "macos", test))]
+pub fn exe_path_macos(steam_path: &Path, fs: &impl FileSystem) -> Option<PathBuf> {
+    ["Contents/MacOS/steam_osx", "Contents/MacOS/Steam"]
+        .into_iter()
+        .map(|exe| steam_path.join(exe))
+        .find(|exe| fs.exists(exe))
+}
+
+#[cfg(any(target_os = "linux", test))]
+pub fn exe_path_linux(steam_path: &Path, fs: &impl FileSystem) -> Option<PathBuf> {
+    // Check if steam_path is the executable itself
+    if fs.is_file(steam_path) {
+        return Some(steam_path.to_path_buf());
+    }
+    ["steam", "steam.sh"]
+        .into_iter()
+        .map(|exe| steam_path.join(exe))
+        .find(|exe| fs.exists(exe))
+}
+
+/// Resolve the Steam executable inside an install directory for the current platform
+pub fn exe_path(steam_path: &Path, fs: &impl FileSystem) -> Option<PathBuf> {
+    #[cfg(windows)]
+    return exe_path_windows(steam_path, fs);
+
+    #[cfg(target_os = "macos")]
+    return exe_path_macos(steam_path, fs);
+
+    #[cfg(target_os = "linux")]
+    return exe_path_linux(steam_path, fs);
+
+    #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+    {
+        let _ = (steam_path, fs);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{HashMap, HashSet};
+
+    /// In-memory filesystem: existing files, existing directories and symlinks
+    #[derive(Default)]
+    struct FakeFs {
+        files: HashSet<PathBuf>,
+        dirs: HashSet<PathBuf>,
+        links: HashMap<PathBuf, PathBuf>,
+    }
+
+    impl FakeFs {
+        fn with_files(files: &[PathBuf]) -> Self {
+            FakeFs {
+                files: files.iter().cloned().collect(),
+                ..Default::default()
+            }
+        }
+
+        fn dir(mut self, path: impl Into<PathBuf>) -> Self {
+            self.dirs.insert(path.into());
+            self
+        }
+    }
+
+    impl FileSystem for FakeFs {
+        fn exists(&self, path: &Path) -> bool {
+            self.files.contains(path) || self.dirs.contains(path) || self.links.contains_key(path)
+        }
+
+        fn is_file(&self, path: &Path) -> bool {
+            self.files.contains(path)
+        }
+
+        fn read_link(&self, path: &Path) -> Option<PathBuf> {
+            self.links.get(path).cloned()
+        }
+    }
+
+    fn no_registry(_: &RegistryValue) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn windows_registry_hit() {
+        let fs = FakeFs::default().dir("D:\\Games\\Steam");
+        let found = find_windows(
+            |value| {
+                (value.key == "SOFTWARE\\Valve\\Steam" && value.name == "InstallPath")
+                    .then(|| "D:\\Games\\Steam".to_string())
+            },
+            &[],
+            &fs,
+        );
+        assert_eq!(found, Some((PathBuf::from("D:\\Games\\Steam"), "HKLM".to_string())));
+    }
+
+    #[test]
+    fn windows_registry_names_the_wow64_view() {
+        let fs = FakeFs::default().dir("C:\\Program Files (x86)\\Steam");
+        let found = find_windows(
+            |value| {
+                (value.key == "SOFTWARE\\WOW6432Node\\Valve\\Steam")
+                    .then(|| "C:\\Program Files (x86)\\Steam".to_string())
+            },
+            &[],
+            &fs,
+        );
+        assert_eq!(
+            found,
+            Some((PathBuf::from("C:\\Program Files (x86)\\Steam"), "HKLM-WOW6432Node".to_string()))
+        );
+    }
+
+    #[test]
+    fn windows_registry_prefers_current_user() {
+        let fs = FakeFs::default().dir("C:\\Steam").dir("D:\\Steam");
+        let found = find_windows(
+            |value| match value.root {
+                RegistryRoot::CurrentUser => Some("C:\\Steam".to_string()),
+                RegistryRoot::LocalMachine => Some("D:\\Steam".to_string()),
+            },
+            &[],
+            &fs,
+        );
+        assert_eq!(found.map(|(_, source)| source), Some("HKCU".to_string()));
+    }
+
+    #[test]
+    fn windows_skips_nonexistent_registry_path() {
+        let program_files = PathBuf::from("C:\\Program Files (x86)");
+        let steam = program_files.join("Steam");
+        let fs = FakeFs::with_files(&[steam.join("steam.exe")]);
+        let found = find_windows(
+            |_| Some("E:\\Uninstalled\\Steam".to_string()),
+            std::slice::from_ref(&program_files),
+            &fs,
+        );
+        assert_eq!(found, Some((steam.clone(), format!("default:{}", steam.display()))));
+    }
+
+    #[test]
+    fn windows_default_path_hit() {
+        let dirs = [PathBuf::from("C:\\Program Files (x86)"), PathBuf::from("C:\\Program Files")];
+        let steam = dirs[1].join("Steam");
+        let fs = FakeFs::with_files(&[steam.join("steam.exe")]);
+        let found = find_windows(no_registry, &dirs, &fs);
+        assert_eq!(found.map(|(path, _)| path), Some(steam));
+    }
+
+    #[test]
+    fn windows_drive_scan() {
+        let drive = PathBuf::from("E:\\");
+        let steam = drive.join("Program Files\\Steam");
+        let fs = FakeFs::with_files(&[steam.join("steam.exe")]).dir(drive);
+        let found = find_windows(no_registry, &[], &fs);
+        assert_eq!(found, Some((steam.clone(), format!("drive:{}", steam.display()))));
+    }
+
+    #[test]
+    fn windows_nothing_found() {
+        // A Steam directory without steam.exe doesn't count as a default install
+        let dirs = [PathBuf::from("C:\\Program Files")];
+        let fs = FakeFs::default().dir(dirs[0].join("Steam"));
+        assert_eq!(find_windows(no_registry, &dirs, &fs), None);
+    }
+
+    #[test]
+    fn macos_mdfind_hit() {
+        let results = [PathBuf::from("/Volumes/Old/Steam.app"), PathBuf::from("/Volumes/Games/Steam.app")];
+        let fs = FakeFs::default().dir("/Volumes/Games/Steam.app").dir("/Applications/Steam.app");
+        let found = find_macos(&results, None, &fs);
+        assert_eq!(found, Some((results[1].clone(), "mdfind".to_string())));
+    }
+
+    #[test]
+    fn macos_default_path_hit() {
+        let fs = FakeFs::default().dir("/Applications/Steam.app");
+        let found = find_macos(&[], Some(Path::new("/Users/me")), &fs);
+        assert_eq!(
+            found,
+            Some((PathBuf::from("/Applications/Steam.app"), "default:/Applications/Steam.app".to_string()))
+        );
+    }
+
+    #[test]
+    fn macos_user_applications() {
+        let fs = FakeFs::default().dir("/Users/me/Applications/Steam.app");
+        let found = find_macos(&[], Some(Path::new("/Users/me")), &fs);
+        assert_eq!(found.map(|(path, _)| path), Some(PathBuf::from("/Users/me/Applications/Steam.app")));
+        assert_eq!(find_macos(&[], None, &fs), None);
+    }
+
+    #[test]
+    fn linux_steam_deck() {
+        let fs = FakeFs::default().dir("/home/deck/.steam/steam").dir("/home/other/.steam");
+        let found = find_linux(true, Some(Path::new("/home/other")), None, &fs);
+        assert_eq!(
+            found,
+            Some((PathBuf::from("/home/deck/.steam/steam"), "steamdeck:/home/deck/.steam/steam".to_string()))
+        );
+        // Off the Deck the same layout is found through the common paths
+        let found = find_linux(false, Some(Path::new("/home/other")), None, &fs);
+        assert_eq!(found.map(|(path, _)| path), Some(PathBuf::from("/home/other/.steam")));
+    }
+
+    #[test]
+    fn linux_which_follows_symlink() {
+        let mut fs = FakeFs::default();
+        fs.links
+            .insert(PathBuf::from("/usr/bin/steam"), PathBuf::from("/usr/lib/steam/steam.sh"));
+        let found = find_linux(false, None, Some(PathBuf::from("/usr/bin/steam")), &fs);
+        assert_eq!(found, Some((PathBuf::from("/usr/lib/steam"), "which".to_string())));
+    }
+
+    #[test]
+    fn linux_which_plain_file() {
+        let fs = FakeFs::with_files(&[PathBuf::from("/usr/games/steam")]);
+        let found = find_linux(false, None, Some(PathBuf::from("/usr/games/steam")), &fs);
+        assert_eq!(found, Some((PathBuf::from("/usr/games/steam"), "which".to_string())));
+    }
+
+    #[test]
+    fn linux_default_path_skips_missing() {
+        let fs = FakeFs::default().dir("/home/me/.local/share/Steam");
+        let found = find_linux(false, Some(Path::new("/home/me")), Some(PathBuf::from("/gone/steam")), &fs);
+        assert_eq!(
+            found,
+            Some((
+                PathBuf::from("/home/me/.local/share/Steam"),
+                "default:/home/me/.local/share/Steam".to_string()
+            ))
+        );
+        assert_eq!(find_linux(false, Some(Path::new("/home/me")), None, &FakeFs::default()), None);
+    }
+
+    #[test]
+    fn windows_exe() {
+        let steam = PathBuf::from("C:\\Steam");
+        let fs = FakeFs::with_files(&[steam.join("steam.exe")]);
+        assert_eq!(exe_path_windows(&steam, &fs), Some(steam.join("steam.exe")));
+        assert_eq!(exe_path_windows(&steam, &FakeFs::default()), None);
+    }
+
+    #[test]
+    fn macos_exe_variants() {
+        let bundle = PathBuf::from("/Applications/Steam.app");
+        let both = FakeFs::with_files(&[
+            bundle.join("Contents/MacOS/steam_osx"),
+            bundle.join("Contents/MacOS/Steam"),
+        ]);
+        assert_eq!(exe_path_macos(&bundle, &both), Some(bundle.join("Contents/MacOS/steam_osx")));
+        let alternative = FakeFs::with_files(&[bundle.join("Contents/MacOS/Steam")]);
+        assert_eq!(exe_path_macos(&bundle, &alternative), Some(bundle.join("Contents/MacOS/Steam")));
+        assert_eq!(exe_path_macos(&bundle, &FakeFs::default()), None);
+    }
+
+    #[test]
+    fn linux_exe_variants() {
+        let exe = PathBuf::from("/usr/games/steam");
+        assert_eq!(exe_path_linux(&exe, &FakeFs::with_files(std::slice::from_ref(&exe))), Some(exe));
+
+        let root = PathBuf::from("/home/me/.steam/steam");
+        let script = FakeFs::with_files(&[root.join("steam.sh")]).dir(root.clone());
+        assert_eq!(exe_path_linux(&root, &script), Some(root.join("steam.sh")));
+        let both = FakeFs::with_files(&[root.join("steam"), root.join("steam.sh")]).dir(root.clone());
+        assert_eq!(exe_path_linux(&root, &both), Some(root.join("steam")));
+        assert_eq!(exe_path_linux(&root, &FakeFs::default().dir(root.clone())), None);
+    }
+}
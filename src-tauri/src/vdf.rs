@@ -0,0 +1,238 @@
+// Minimal parser for Valve's KeyValues (VDF) text format, as used by
+// libraryfolders.vdf and loginusers.vdf
+
+/// A parsed VDF value: either a string or a nested block of key/value pairs
+#[derive(Debug, Clone, PartialEq)]
+pub enum VdfValue {
+    String(String),
+    Object(Vec<(String, VdfValue)>),
+}
+
+impl VdfValue {
+    /// Get the first child with the given key (case-insensitive, like Steam itself)
+    pub fn get(&self, key: &str) -> Option<&VdfValue> {
+        match self {
+            VdfValue::Object(entries) => entries
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v),
+            VdfValue::String(_) => None,
+        }
+    }
+
+    /// Get a child string value by key
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(VdfValue::as_str)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            VdfValue::String(s) => Some(s),
+            VdfValue::Object(_) => None,
+        }
+    }
+
+    /// Key/value pairs of a block, empty for string values
+    pub fn entries(&self) -> &[(String, VdfValue)] {
+        match self {
+            VdfValue::Object(entries) => entries,
+            VdfValue::String(_) => &[],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Str(String),
+    Open,
+    Close,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '{' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            '}' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '/' => {
+                // Line comment
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                } else {
+                    return Err("Unexpected '/' in VDF".to_string());
+                }
+            }
+            '[' => {
+                // Platform conditionals like [$WIN32] are ignored
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                }
+            }
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some(other) => value.push(other),
+                            None => return Err("Unterminated escape in VDF".to_string()),
+                        },
+                        Some(other) => value.push(other),
+                        None => return Err("Unterminated string in VDF".to_string()),
+                    }
+                }
+                tokens.push(Token::Str(value));
+            }
+            _ => {
+                // Unquoted token, ends at whitespace or a brace
+                let mut value = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '{' || c == '}' || c == '"' {
+                        break;
+                    }
+                    value.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Str(value));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_entries<I: Iterator<Item = Token>>(
+    tokens: &mut I,
+    nested: bool,
+) -> Result<Vec<(String, VdfValue)>, String> {
+    let mut entries = Vec::new();
+
+    loop {
+        let key = match tokens.next() {
+            Some(Token::Str(key)) => key,
+            Some(Token::Close) if nested => return Ok(entries),
+            None if !nested => return Ok(entries),
+            Some(Token::Close) => return Err("Unexpected '}' in VDF".to_string()),
+            Some(Token::Open) => return Err("Unexpected '{' in VDF".to_string()),
+            None => return Err("Unexpected end of VDF, missing '}'".to_string()),
+        };
+
+        let value = match tokens.next() {
+            Some(Token::Str(value)) => VdfValue::String(value),
+            Some(Token::Open) => VdfValue::Object(parse_entries(tokens, true)?),
+            _ => return Err(format!("Missing value for key \"{}\" in VDF", key)),
+        };
+
+        entries.push((key, value));
+    }
+}
+
+/// Parse a VDF document into its top-level block
+pub fn parse(input: &str) -> Result<VdfValue, String> {
+    let mut tokens = tokenize(input)?.into_iter();
+    Ok(VdfValue::Object(parse_entries(&mut tokens, false)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(value: &str) -> VdfValue {
+        VdfValue::String(value.to_string())
+    }
+
+    #[test]
+    fn parses_nested_objects() {
+        let doc = parse(
+            r#"
+            "libraryfolders"
+            {
+                "0"
+                {
+                    "path"    "C:\\Program Files (x86)\\Steam"
+                    "apps" { "228980" "123" }
+                }
+            }
+            "#,
+        )
+        .unwrap();
+
+        let folder = doc.get("libraryfolders").and_then(|v| v.get("0")).unwrap();
+        assert_eq!(folder.get_str("path"), Some(r"C:\Program Files (x86)\Steam"));
+        assert_eq!(
+            folder.get("apps").unwrap().entries(),
+            [("228980".to_string(), string("123"))]
+        );
+    }
+
+    #[test]
+    fn keys_are_case_insensitive() {
+        let doc = parse(r#""Users" { "MostRecent" "1" }"#).unwrap();
+        assert_eq!(doc.get("users").and_then(|v| v.get_str("mostrecent")), Some("1"));
+    }
+
+    #[test]
+    fn unescapes_quoted_strings() {
+        let doc = parse(r#""name" "say \"hi\"\tnow\nplease \\ done""#).unwrap();
+        assert_eq!(doc.get_str("name"), Some("say \"hi\"\tnow\nplease \\ done"));
+    }
+
+    #[test]
+    fn skips_comments_and_conditionals() {
+        let doc = parse(
+            r#"
+            // leading comment
+            "root"
+            {
+                "a" "1" // trailing comment
+                "b" "2" [$WIN32]
+            }
+            "#,
+        )
+        .unwrap();
+        let root = doc.get("root").unwrap();
+        assert_eq!(root.entries(), [("a".to_string(), string("1")), ("b".to_string(), string("2"))]);
+    }
+
+    #[test]
+    fn accepts_unquoted_tokens() {
+        let doc = parse("root { key value }").unwrap();
+        assert_eq!(doc.get("root").and_then(|v| v.get_str("key")), Some("value"));
+    }
+
+    #[test]
+    fn rejects_unterminated_input() {
+        assert!(parse(r#""root" { "key" "value"#).unwrap_err().contains("Unterminated string"));
+        assert!(parse(r#""key" "value\"#).unwrap_err().contains("Unterminated escape"));
+        assert!(parse(r#""root" { "key" "value""#).unwrap_err().contains("missing '}'"));
+    }
+
+    #[test]
+    fn rejects_malformed_structure() {
+        assert!(parse(r#""key" "value" }"#).unwrap_err().contains("Unexpected '}'"));
+        assert!(parse(r#"{ "key" "value" }"#).unwrap_err().contains("Unexpected '{'"));
+        assert!(parse(r#""key""#).unwrap_err().contains("Missing value for key \"key\""));
+        assert!(parse(r#""key" / "value""#).unwrap_err().contains("Unexpected '/'"));
+    }
+}
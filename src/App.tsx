@@ -22,6 +22,7 @@ import {
 import { useStyles as useGlobalStyles, containerGap } from "./styles";
 import { AppHeader, LobbyTab, VPNTab, SteamChinaTab, FirewallTab } from "./components";
 import { useTheme, useCore } from "./hooks";
+import { formatError } from "./utils/helpers";
 
 const useAppStyles = makeStyles({
   container: {
@@ -57,7 +58,7 @@ function App() {
         await startCore();
       } catch (err) {
         // 启动失败时通知用户
-        const errorMsg = err instanceof Error ? err.message : String(err);
+        const errorMsg = formatError(err);
         setErrorMsg(`核心服务自动启动失败: ${errorMsg}`);
         setTimeout(() => setErrorMsg(null), 5000);
       }
@@ -92,7 +93,7 @@ function App() {
         showSuccess("核心服务已停止");
       }
     } catch (err) {
-      const errorMsg = err instanceof Error ? err.message : String(err);
+      const errorMsg = formatError(err);
       handleError(errorMsg || coreError || "核心服务操作失败");
     }
   };
@@ -19,6 +19,7 @@ import {
 } from "@fluentui/react-icons";
 import { useStyles as useGlobalStyles, containerGap, sectionGap } from "../styles";
 import { FirewallStatusResponse, FirewallToggleResponse } from "../types";
+import { formatError } from "../utils/helpers";
 
 const useLocalStyles = makeStyles({
   container: {
@@ -115,7 +116,7 @@ export function FirewallTab({ onError, onSuccess }: FirewallTabProps) {
       const status = await invoke<FirewallStatusResponse>("get_firewall_status");
       setFirewallStatus(status);
     } catch (e) {
-      onError(`获取防火墙状态失败: ${e}`);
+      onError(`获取防火墙状态失败: ${formatError(e)}`);
     } finally {
       setLoading(false);
     }
@@ -136,7 +137,7 @@ export function FirewallTab({ onError, onSuccess }: FirewallTabProps) {
         onError(result.message);
       }
     } catch (e) {
-      onError(`${enabled ? "开启" : "关闭"}防火墙失败: ${e}`);
+      onError(`${enabled ? "开启" : "关闭"}防火墙失败: ${formatError(e)}`);
     } finally {
       setToggling(false);
     }
@@ -37,6 +37,7 @@ import {
   GetFriendLobbiesResponse,
   InviteFriendResponse,
 } from "../types";
+import { formatError } from "../utils/helpers";
 
 const useLocalStyles = makeStyles({
   container: {
@@ -134,7 +135,7 @@ export function LobbyTab({ onError, onSuccess }: LobbyTabProps) {
       }
     } catch (e) {
       console.error(e);
-      onError(String(e));
+      onError(formatError(e));
     } finally {
       setIsCreatingLobby(false);
     }
@@ -153,7 +154,7 @@ export function LobbyTab({ onError, onSuccess }: LobbyTabProps) {
       }
     } catch (e) {
       console.error(e);
-      onError(String(e));
+      onError(formatError(e));
     }
   };
 
@@ -164,7 +165,7 @@ export function LobbyTab({ onError, onSuccess }: LobbyTabProps) {
       onSuccess("已离开大厅");
     } catch (e) {
       console.error(e);
-      onError(String(e));
+      onError(formatError(e));
     }
   };
 
@@ -174,7 +175,7 @@ export function LobbyTab({ onError, onSuccess }: LobbyTabProps) {
       setFriendLobbies(res.lobbies);
     } catch (e) {
       console.error(e);
-      onError(String(e));
+      onError(formatError(e));
     }
   };
 
@@ -190,7 +191,7 @@ export function LobbyTab({ onError, onSuccess }: LobbyTabProps) {
       }
     } catch (e) {
       console.error(e);
-      onError(String(e));
+      onError(formatError(e));
     }
   };
 
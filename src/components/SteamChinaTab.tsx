@@ -4,6 +4,12 @@ import {
   Text,
   Button,
   Divider,
+  Dialog,
+  DialogSurface,
+  DialogBody,
+  DialogTitle,
+  DialogContent,
+  DialogActions,
   MessageBar,
   MessageBarBody,
   makeStyles,
@@ -12,6 +18,7 @@ import {
 import { ArrowSyncRegular, PlayRegular } from "@fluentui/react-icons";
 import { useStyles as useGlobalStyles, containerGap, sectionGap } from "../styles";
 import { FindSteamPathResponse, GetSteamStatusResponse, RestartSteamChinaResponse } from "../types";
+import { formatError } from "../utils/helpers";
 
 const useLocalStyles = makeStyles({
   container: {
@@ -90,6 +97,8 @@ export function SteamChinaTab({ onError, onSuccess, coreRunning, onStopCore }: S
   const [steamProcessId, setSteamProcessId] = useState<number | null>(null);
   const [isRestartingSteam, setIsRestartingSteam] = useState(false);
   const [isStoppingCore, setIsStoppingCore] = useState(false);
+  // Confirmation token issued when the dialog opens, the dialog is shown while it is set
+  const [restartToken, setRestartToken] = useState<string | null>(null);
 
   const styles = useGlobalStyles();
   const localStyles = useLocalStyles();
@@ -118,7 +127,22 @@ export function SteamChinaTab({ onError, onSuccess, coreRunning, onStopCore }: S
     return () => clearInterval(interval);
   }, [refreshSteamStatus]);
 
-  const handleRestartSteamChina = async () => {
+  const handleRequestRestart = async () => {
+    try {
+      // The restart only goes through with a fresh confirmation token
+      const { token } = await invoke<{ token: string }>("request_steam_restart_token");
+      setRestartToken(token);
+    } catch (e) {
+      console.error(e);
+      onError(formatError(e));
+    }
+  };
+
+  const handleConfirmRestart = async () => {
+    const token = restartToken;
+    setRestartToken(null);
+    if (!token) return;
+
     // 如果 Core 正在运行，先停止它
     if (coreRunning && onStopCore) {
       setIsStoppingCore(true);
@@ -129,7 +153,7 @@ export function SteamChinaTab({ onError, onSuccess, coreRunning, onStopCore }: S
         await new Promise(resolve => setTimeout(resolve, 500));
       } catch (e) {
         console.error("Failed to stop core:", e);
-        onError(`停止核心服务失败: ${String(e)}`);
+        onError(`停止核心服务失败: ${formatError(e)}`);
         setIsStoppingCore(false);
         return;
       } finally {
@@ -139,7 +163,7 @@ export function SteamChinaTab({ onError, onSuccess, coreRunning, onStopCore }: S
 
     setIsRestartingSteam(true);
     try {
-      const res = await invoke<RestartSteamChinaResponse>("restart_steam_china");
+      const res = await invoke<RestartSteamChinaResponse>("restart_steam_china", { token });
       if (res.success) {
         onSuccess(res.message);
         // Refresh Steam status after a short delay
@@ -149,7 +173,7 @@ export function SteamChinaTab({ onError, onSuccess, coreRunning, onStopCore }: S
       }
     } catch (e) {
       console.error(e);
-      onError(String(e));
+      onError(formatError(e));
     } finally {
       setIsRestartingSteam(false);
     }
@@ -230,7 +254,7 @@ export function SteamChinaTab({ onError, onSuccess, coreRunning, onStopCore }: S
             <div className={localStyles.actionRow}>
               <Button
                 appearance="primary"
-                onClick={handleRestartSteamChina}
+                onClick={handleRequestRestart}
                 disabled={isRestartingSteam || isStoppingCore || !steamPath}
                 icon={isSteamRunning ? <ArrowSyncRegular /> : <PlayRegular />}
               >
@@ -267,6 +291,33 @@ export function SteamChinaTab({ onError, onSuccess, coreRunning, onStopCore }: S
           </MessageBarBody>
         </MessageBar>
       </div>
+
+      <Dialog
+        open={restartToken !== null}
+        onOpenChange={(_, data) => {
+          if (!data.open) setRestartToken(null);
+        }}
+      >
+        <DialogSurface>
+          <DialogBody>
+            <DialogTitle>{isSteamRunning ? "重启为中国区" : "以中国区启动"}</DialogTitle>
+            <DialogContent>
+              {isSteamRunning
+                ? "将关闭当前 Steam 并以 -steamchina 参数重新启动，未保存的游戏进度可能会丢失。"
+                : "将以 -steamchina 参数启动 Steam。"}
+              {coreRunning && " 核心服务会先被停止。"}
+            </DialogContent>
+            <DialogActions>
+              <Button appearance="secondary" onClick={() => setRestartToken(null)}>
+                取消
+              </Button>
+              <Button appearance="primary" onClick={handleConfirmRestart}>
+                确认
+              </Button>
+            </DialogActions>
+          </DialogBody>
+        </DialogSurface>
+      </Dialog>
     </div>
   );
 }
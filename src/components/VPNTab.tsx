@@ -17,7 +17,7 @@ import {
 } from "@fluentui/react-components";
 import { ArrowSyncRegular } from "@fluentui/react-icons";
 import { useStyles as useGlobalStyles, containerGap, sectionGap } from "../styles";
-import { ipToString, formatBytes } from "../utils/helpers";
+import { ipToString, formatBytes, formatError } from "../utils/helpers";
 import { VPNStats, VPNRoute, GetVPNStatusResponse, GetVPNRoutingTableResponse } from "../types";
 
 const useLocalStyles = makeStyles({
@@ -150,7 +150,7 @@ export function VPNTab({ onError, onVpnStatusChange }: VPNTabProps) {
       setVpnRoutes(res.routes);
     } catch (e) {
       console.error(e);
-      onError(String(e));
+      onError(formatError(e));
     }
   };
 
@@ -1,5 +1,7 @@
 import { useState, useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { formatError } from "../utils/helpers";
+import type { CommandError } from "../types";
 
 interface CoreStatusResponse {
   is_running: boolean;
@@ -24,6 +26,8 @@ export function useCore() {
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
   const [version, setVersion] = useState<string | null>(null);
+  // Path searched for the core executable when it could not be found
+  const [missingCorePath, setMissingCorePath] = useState<string | null>(null);
 
   // Fetch core version
   const fetchVersion = useCallback(async () => {
@@ -51,7 +55,7 @@ export function useCore() {
         setVersion(null);
       }
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err));
+      setError(formatError(err));
     }
   }, [fetchVersion]);
 
@@ -61,16 +65,25 @@ export function useCore() {
     setError(null);
     try {
       const response = await invoke<CoreControlResponse>("start_core");
+      setMissingCorePath(null);
       if (response.success) {
         setIsRunning(response.is_running);
         setPid(response.pid);
+        // The socket can bind a beat after start, this retries until the core answers
+        invoke<CoreVersionResponse>("get_core_version_after_start")
+          .then((version) => setVersion(version.version))
+          .catch(() => setVersion(null));
       } else {
         setError(response.message);
         throw new Error(response.message);
       }
       return response;
     } catch (err) {
-      const errorMsg = err instanceof Error ? err.message : String(err);
+      const e = err as CommandError;
+      if (e?.kind === "core_not_found") {
+        setMissingCorePath(e.detail.searched_path);
+      }
+      const errorMsg = formatError(err);
       setError(errorMsg);
       throw err;
     } finally {
@@ -78,6 +91,17 @@ export function useCore() {
     }
   }, []);
 
+  // Point at a core executable chosen by the user, then start it
+  const locateCore = useCallback(async (path: string) => {
+    try {
+      await invoke("set_core_path_override", { path });
+    } catch (err) {
+      setError(formatError(err));
+      throw err;
+    }
+    return await startCore();
+  }, [startCore]);
+
   // Stop core
   const stopCore = useCallback(async () => {
     setLoading(true);
@@ -93,7 +117,7 @@ export function useCore() {
       }
       return response;
     } catch (err) {
-      const errorMsg = err instanceof Error ? err.message : String(err);
+      const errorMsg = formatError(err);
       setError(errorMsg);
       throw err;
     } finally {
@@ -126,8 +150,10 @@ export function useCore() {
     loading,
     error,
     version,
+    missingCorePath,
     checkStatus,
     startCore,
+    locateCore,
     stopCore,
     toggleCore,
   };
@@ -94,3 +94,22 @@ export interface FirewallToggleResponse {
   success: boolean;
   message: string;
 }
+
+// Where the socket path in use came from; CONNECT_TOOL_SOCKET takes precedence over the saved path
+export type SocketPathSource = "env" | "settings" | "default";
+
+// Error returned by every command when it fails
+export type CommandError =
+  | { kind: "not_connected"; detail: { address: string } }
+  | { kind: "timeout" }
+  | { kind: "rpc"; detail: { code: number; message: string } }
+  | { kind: "cancelled" }
+  | { kind: "core_starting" }
+  | { kind: "core_absent"; detail: { path: string; source: SocketPathSource } }
+  | { kind: "socket_dir_missing"; detail: { dir: string; path: string; source: SocketPathSource } }
+  | { kind: "socket_permission_denied"; detail: { path: string; source: SocketPathSource; detail: string } }
+  | { kind: "socket_refused"; detail: { path: string; source: SocketPathSource; detail: string } }
+  | { kind: "firewall"; detail: { code: "access_denied" | "blocked_by_policy" | "unknown"; raw: string } }
+  | { kind: "core_not_found"; detail: { searched_path: string } }
+  | { kind: "read_only" }
+  | { kind: "internal"; detail: string };
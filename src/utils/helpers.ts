@@ -1,3 +1,5 @@
+import { CommandError, SocketPathSource } from "../types";
+
 /**
  * Convert IP number to string format
  */
@@ -15,3 +17,61 @@ export const formatBytes = (bytes: number): string => {
   const i = Math.floor(Math.log(bytes) / Math.log(k));
   return parseFloat((bytes / Math.pow(k, i)).toFixed(2)) + " " + sizes[i];
 };
+
+const socketPathSourceLabels: Record<SocketPathSource, string> = {
+  env: "环境变量 CONNECT_TOOL_SOCKET",
+  settings: "已保存的设置",
+  default: "默认路径",
+};
+
+/**
+ * Describe which socket was attempted and why, for socket connection errors
+ */
+const describeSocket = (path: string, source: SocketPathSource): string =>
+  `套接字 ${path}（来源: ${socketPathSourceLabels[source]}；优先级: 环境变量 CONNECT_TOOL_SOCKET > 已保存的设置 > 默认路径）`;
+
+/**
+ * Convert an error thrown by invoke into a displayable message
+ */
+export const formatError = (err: unknown): string => {
+  if (err instanceof Error) return err.message;
+  if (typeof err === "object" && err !== null && "kind" in err) {
+    const e = err as CommandError;
+    switch (e.kind) {
+      case "not_connected":
+        return `核心服务未连接（${e.detail.address}）`;
+      case "timeout":
+        return "核心服务响应超时";
+      case "rpc":
+        return e.detail.message;
+      case "cancelled":
+        return "操作已取消";
+      case "core_starting":
+        return "核心服务正在启动";
+      case "core_absent":
+        return `核心服务未运行，${describeSocket(e.detail.path, e.detail.source)} 不存在`;
+      case "socket_dir_missing":
+        return `套接字目录 ${e.detail.dir} 不存在，${describeSocket(e.detail.path, e.detail.source)} 无法创建`;
+      case "socket_permission_denied":
+        return `无权访问${describeSocket(e.detail.path, e.detail.source)}，它可能属于其他用户，请以相同用户运行核心服务`;
+      case "socket_refused":
+        return `${describeSocket(e.detail.path, e.detail.source)} 无响应，可能是核心服务异常退出后残留的文件`;
+      case "firewall":
+        switch (e.detail.code) {
+          case "access_denied":
+            return "修改防火墙需要管理员权限，请以管理员身份运行";
+          case "blocked_by_policy":
+            return "防火墙设置受组策略管理，请联系系统管理员";
+          default:
+            return e.detail.raw;
+        }
+      case "core_not_found":
+        return `未找到核心程序: ${e.detail.searched_path}`;
+      case "read_only":
+        return "只读模式下不允许此操作";
+      case "internal":
+        return e.detail;
+    }
+  }
+  return String(err);
+};